@@ -42,11 +42,14 @@ fn main() {
 
 fn print_results_with_pitch(result: FindWithNhkResult) {
     match result {
+        FindWithNhkResult::NotFound => {
+            println!("This word is not in the dictionary.");
+        }
         FindWithNhkResult::NoHomophones => {
             println!("This word has no homophones.");
         }
-        FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones } => {
-            println!("Unique match found!");
+        FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, target_readings, .. } => {
+            println!("Unique match found! (reading: {})", target_readings.join(", "));
             
             if !true_homophones.is_empty() {
                 println!("\nTrue homophones (same pitch):");
@@ -61,11 +64,17 @@ fn print_results_with_pitch(result: FindWithNhkResult) {
             println!("\nSummary: {} true homophones, {} fake homophones", 
                 true_homophones.len(), different_pitch_homophones.len());
         }
-        FindWithNhkResult::MultipleMatches { homophones } => {
+        FindWithNhkResult::MultipleMatches { homophones, .. } => {
             println!("Multiple matches found (searched by reading):");
             print_words_table(&homophones);
             println!("\nTotal homophones: {}", homophones.len());
         }
+        FindWithNhkResult::Affix { affixes } => {
+            println!("Suffix accents in compounds:");
+            for affix in affixes {
+                println!("  〜{} ({}): {:?}", affix.text, affix.reading, affix.rule);
+            }
+        }
     }
 }
 
@@ -0,0 +1,110 @@
+#ifndef JAYDAR_H
+#define JAYDAR_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Discriminant of `FindWithNhkResult`
+typedef enum JaydarResultKind {
+  JaydarResultKind_NoHomophones = 0,
+  JaydarResultKind_UniqueMatch = 1,
+  JaydarResultKind_MultipleMatches = 2,
+  JaydarResultKind_NotFound = 3,
+  // A suffix such as 〜あめ; its rules are in the JSON result only
+  JaydarResultKind_Affix = 4,
+} JaydarResultKind;
+
+// How a word was reached from the input, see `MatchKind`
+typedef enum JaydarMatchKind {
+  JaydarMatchKind_KanjiElement = 0,
+  JaydarMatchKind_Reading = 1,
+  JaydarMatchKind_KatakanaConversion = 2,
+  JaydarMatchKind_KatakanaSelfEntry = 3,
+  JaydarMatchKind_SharedReading = 4,
+} JaydarMatchKind;
+
+// A homophone with its pitch accents
+typedef struct JaydarWord {
+  // NUL-terminated UTF-8
+  char *text;
+  // NUL-terminated UTF-8, as the dictionary spells it (katakana for loanwords and many names)
+  char *reading;
+  uint32_t frequency_score;
+  bool is_common;
+  // Pitch accents in order of preference, empty if unknown
+  uint8_t *pitch_accent;
+  size_t pitch_accent_len;
+  enum JaydarMatchKind match_kind;
+} JaydarWord;
+
+// An owned array of words
+typedef struct JaydarWordArray {
+  struct JaydarWord *words;
+  size_t len;
+} JaydarWordArray;
+
+// Result of `jaydar_find_with_nhk`
+typedef struct JaydarResult {
+  enum JaydarResultKind kind;
+  // `UniqueMatch`: the true homophones (same pitch).
+  // `MultipleMatches`: all homophones. Empty for `NoHomophones` and `NotFound`.
+  struct JaydarWordArray homophones;
+  // `UniqueMatch` only: homophones with a different pitch accent
+  struct JaydarWordArray different_pitch_homophones;
+  // Readings the input resolved to, as NUL-terminated UTF-8 strings.
+  // Empty for `NoHomophones` and `NotFound`.
+  char **target_readings;
+  size_t target_readings_len;
+} JaydarResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Find homophones with pitch accent data.
+//
+// Returns null if `word` is null or not valid UTF-8. Free the result with
+// `jaydar_result_free`.
+//
+// # Safety
+//
+// `word` must be null or point to a NUL-terminated string.
+struct JaydarResult *jaydar_find_with_nhk(const char *word);
+
+// Find homophones with pitch accent data, serialized as JSON.
+//
+// The JSON object has a `kind` field holding the `FindWithNhkResult`
+// variant name. Returns null if `word` is null or not valid UTF-8. Free the
+// result with `jaydar_string_free`.
+//
+// # Safety
+//
+// `word` must be null or point to a NUL-terminated string.
+char *jaydar_find_with_nhk_json(const char *word);
+
+// Free a result returned by `jaydar_find_with_nhk`. Null is ignored.
+//
+// # Safety
+//
+// `result` must be null or a pointer returned by `jaydar_find_with_nhk`
+// that has not been freed yet.
+void jaydar_result_free(struct JaydarResult *result);
+
+// Free a string returned by `jaydar_find_with_nhk_json`. Null is ignored.
+//
+// # Safety
+//
+// `s` must be null or a pointer returned by this library that has not been
+// freed yet.
+void jaydar_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JAYDAR_H */
@@ -0,0 +1,135 @@
+//! Accent of suffixes in compounds, from the `〜` entries of the NHK data.
+//!
+//! NHK lists bound morphemes such as 〜あめ (雨) and 〜かい (会) with the
+//! accent a compound takes when they end it: 大雨 おおあめ is accented on the
+//! first mora of あめ, 日本語 にほんご is heiban, 千葉県 ちばけん falls right
+//! before けん.
+
+use crate::kana_utils::katakana_to_hiragana;
+use crate::nhk_data::NHK_PITCH_DATA;
+use crate::pitch::mora_count;
+use std::sync::OnceLock;
+
+/// Where a compound ending in a suffix is accented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AffixAccentRule {
+    /// No downstep: 〜語 in 日本語[0]
+    Heiban,
+    /// Downstep after the last mora before the suffix: 〜県 in 千葉県[2]
+    BeforeSuffix,
+    /// Downstep after the first mora of the suffix: 〜雨 in 大雨[3]
+    SuffixFirstMora,
+}
+
+impl AffixAccentRule {
+    /// NHK notation: 0, 3 and 4
+    fn from_nhk(code: u8) -> Option<AffixAccentRule> {
+        match code {
+            0 => Some(AffixAccentRule::Heiban),
+            3 => Some(AffixAccentRule::BeforeSuffix),
+            4 => Some(AffixAccentRule::SuffixFirstMora),
+            _ => None,
+        }
+    }
+
+    /// Accent number of a compound whose part before the suffix has `stem_morae` morae
+    pub fn compound_accent(self, stem_morae: usize) -> u8 {
+        match self {
+            AffixAccentRule::Heiban => 0,
+            AffixAccentRule::BeforeSuffix => stem_morae as u8,
+            AffixAccentRule::SuffixFirstMora => stem_morae as u8 + 1,
+        }
+    }
+}
+
+/// A suffix with its compound accent rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AffixAccent {
+    /// Reading in hiragana, without 〜 (voiced where NHK lists it so: がし for 菓子)
+    pub reading: String,
+    /// Spelling, without 〜
+    pub text: String,
+    pub rule: AffixAccentRule,
+}
+
+/// Every suffix in the NHK data
+pub fn affix_accents() -> &'static [AffixAccent] {
+    static AFFIXES: OnceLock<Vec<AffixAccent>> = OnceLock::new();
+    AFFIXES.get_or_init(|| {
+        NHK_PITCH_DATA.iter()
+            .filter_map(|&(reading, text, accents)| {
+                let reading = reading.strip_prefix('〜')?;
+                let rule = AffixAccentRule::from_nhk(*accents.first()?)?;
+                Some(AffixAccent {
+                    reading: reading.to_string(),
+                    text: text.trim_start_matches('〜').to_string(),
+                    rule,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Suffixes spelled or read as `query`, with or without a leading 〜 or ～
+pub fn find_affix(query: &str) -> Vec<&'static AffixAccent> {
+    let query = query.trim_start_matches(['〜', '～']);
+    let hiragana = katakana_to_hiragana(query);
+    affix_accents().iter()
+        .filter(|a| a.text == query || a.reading == hiragana)
+        .collect()
+}
+
+/// Suffixes read as one of `readings` (hiragana)
+pub(crate) fn read_as(readings: &[String]) -> Vec<AffixAccent> {
+    affix_accents().iter()
+        .filter(|a| readings.contains(&a.reading))
+        .cloned()
+        .collect()
+}
+
+/// Rule of the suffix spelled `text` and read `reading`, if NHK lists it
+pub(crate) fn suffix_rule(reading: &str, text: &str) -> Option<AffixAccentRule> {
+    affix_accents().iter()
+        .find(|a| a.text == text && a.reading == reading)
+        .map(|a| a.rule)
+}
+
+/// Accent of a word ending in a known suffix, by the longest such suffix
+///
+/// The part before the suffix can be any word, 大 in 大雨 need not be in the
+/// NHK data.
+pub(crate) fn suffix_compound_accent(text: &str, reading: &str) -> Option<u8> {
+    let reading = katakana_to_hiragana(reading);
+    affix_accents().iter()
+        .filter(|a| text.len() > a.text.len() && text.ends_with(&a.text))
+        .filter(|a| reading.len() > a.reading.len() && reading.ends_with(&a.reading))
+        .max_by_key(|a| (a.reading.len(), a.text.len()))
+        .map(|a| a.rule.compound_accent(mora_count(&reading[..reading.len() - a.reading.len()])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affix_table() {
+        let ame = find_affix("〜あめ");
+        assert!(ame.iter().any(|a| a.text == "雨" && a.rule == AffixAccentRule::SuffixFirstMora));
+        assert!(ame.iter().any(|a| a.text == "飴" && a.rule == AffixAccentRule::BeforeSuffix));
+        assert_eq!(find_affix("会"), find_affix("かい").into_iter().filter(|a| a.text == "会").collect::<Vec<_>>());
+        // Entries spelled with 〜 lose it too
+        assert!(find_affix("がかり").iter().any(|a| a.text == "がかり"));
+        assert!(affix_accents().iter().all(|a| !a.reading.starts_with('〜')));
+    }
+
+    #[test]
+    fn test_suffix_compound_accent() {
+        assert_eq!(suffix_compound_accent("大雨", "おおあめ"), Some(3));
+        assert_eq!(suffix_compound_accent("日本語", "にほんご"), Some(0));
+        assert_eq!(suffix_compound_accent("千葉県", "ちばけん"), Some(2));
+        // A suffix alone is not a compound
+        assert_eq!(suffix_compound_accent("雨", "あめ"), None);
+    }
+}
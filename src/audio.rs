@@ -0,0 +1,291 @@
+//! Synthesized audio of pitch accent contours (feature `audio`).
+//!
+//! Each mora is a harmonic tone at a low or high pitch, shaped by rough
+//! formants of its vowel, with short glides between morae and a trailing
+//! particle が. The output is deterministic, so drills can be generated
+//! offline and cached.
+
+use crate::kana_utils::katakana_to_hiragana;
+use crate::pitch::{Pitch, pitch_pattern, split_morae};
+use crate::{FindWithNhkResult, MatchKind, WordFrequencyWithPitch};
+use std::f32::consts::PI;
+use std::path::Path;
+
+/// Voice settings for [`synthesize`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SynthesisOptions {
+    pub sample_rate: u32,
+    pub mora_milliseconds: u32,
+    /// Fundamental frequency of low morae
+    pub low_hz: f32,
+    /// Fundamental frequency of high morae
+    pub high_hz: f32,
+    /// Append the particle が, which tells odaka from heiban
+    pub particle: bool,
+}
+
+impl Default for SynthesisOptions {
+    fn default() -> Self {
+        SynthesisOptions {
+            sample_rate: 22050,
+            mora_milliseconds: 180,
+            low_hz: 120.0,
+            high_hz: 170.0,
+            particle: true,
+        }
+    }
+}
+
+/// Two readings of one sound, for telling their accents apart
+#[derive(Debug, Clone, PartialEq)]
+pub struct PitchContrast {
+    pub first: WordFrequencyWithPitch,
+    pub second: WordFrequencyWithPitch,
+    /// WAV file of `first`, a pause, then `second`
+    pub wav: Vec<u8>,
+}
+
+/// Silence between the two words of a [`PitchContrast`]
+const PAUSE_MILLISECONDS: u32 = 400;
+/// Length of the pitch glide into each mora
+const GLIDE_MILLISECONDS: f32 = 30.0;
+/// Fade in and out of voiced stretches, against clicks
+const FADE_MILLISECONDS: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sound {
+    Vowel([f32; 2]),
+    /// っ
+    Silence,
+}
+
+/// First and second formants of a vowel, in Hz
+fn formants(vowel: char) -> [f32; 2] {
+    match vowel {
+        'a' => [800.0, 1200.0],
+        'i' => [300.0, 2300.0],
+        'u' => [350.0, 1300.0],
+        'e' => [500.0, 1900.0],
+        'o' => [500.0, 850.0],
+        // ん
+        _ => [250.0, 1500.0],
+    }
+}
+
+fn vowel(kana: char) -> Option<char> {
+    let vowel = match kana {
+        'あ' | 'か' | 'さ' | 'た' | 'な' | 'は' | 'ま' | 'や' | 'ら' | 'わ' | 'が' | 'ざ' | 'だ' | 'ば' | 'ぱ' | 'ぁ' | 'ゃ' | 'ゎ' => 'a',
+        'い' | 'き' | 'し' | 'ち' | 'に' | 'ひ' | 'み' | 'り' | 'ぎ' | 'じ' | 'ぢ' | 'び' | 'ぴ' | 'ぃ' | 'ゐ' => 'i',
+        'う' | 'く' | 'す' | 'つ' | 'ぬ' | 'ふ' | 'む' | 'ゆ' | 'る' | 'ぐ' | 'ず' | 'づ' | 'ぶ' | 'ぷ' | 'ぅ' | 'ゅ' | 'ゔ' => 'u',
+        'え' | 'け' | 'せ' | 'て' | 'ね' | 'へ' | 'め' | 'れ' | 'げ' | 'ぜ' | 'で' | 'べ' | 'ぺ' | 'ぇ' | 'ゑ' => 'e',
+        'お' | 'こ' | 'そ' | 'と' | 'の' | 'ほ' | 'も' | 'よ' | 'ろ' | 'を' | 'ご' | 'ぞ' | 'ど' | 'ぼ' | 'ぽ' | 'ぉ' | 'ょ' => 'o',
+        'ん' => 'n',
+        _ => return None,
+    };
+    Some(vowel)
+}
+
+/// Sound of each mora: the vowel of its last kana, ー repeating the one before
+fn sounds(reading: &str) -> Vec<Sound> {
+    let mut sounds: Vec<Sound> = Vec::new();
+    for mora in split_morae(&katakana_to_hiragana(reading)) {
+        let last = mora.chars().last().unwrap_or('ー');
+        let sound = match last {
+            'っ' => Sound::Silence,
+            'ー' => sounds.last().copied().unwrap_or(Sound::Vowel(formants('a'))),
+            c => Sound::Vowel(formants(vowel(c).unwrap_or('a'))),
+        };
+        sounds.push(sound);
+    }
+    sounds
+}
+
+/// Target fundamental frequency of each mora, then of the particle if enabled
+fn contour(reading: &str, accent: u8, options: &SynthesisOptions) -> Vec<f32> {
+    let mut pattern = pitch_pattern(reading, accent);
+    if !options.particle {
+        pattern.pop();
+    }
+    pattern.into_iter()
+        .map(|p| match p {
+            Pitch::High => options.high_hz,
+            Pitch::Low => options.low_hz,
+        })
+        .collect()
+}
+
+/// Samples (mono, 16-bit) of `reading` said with an NHK accent number
+pub fn synthesize(reading: &str, accent: u8, options: &SynthesisOptions) -> Vec<i16> {
+    let mut sounds = sounds(reading);
+    if options.particle {
+        sounds.push(Sound::Vowel(formants('a')));
+    }
+    let targets = contour(reading, accent, options);
+
+    let rate = options.sample_rate as f32;
+    let mora_samples = (options.sample_rate * options.mora_milliseconds / 1000) as usize;
+    let glide_samples = (GLIDE_MILLISECONDS * rate / 1000.0) as usize;
+    let fade_samples = (FADE_MILLISECONDS * rate / 1000.0).max(1.0);
+    let total = mora_samples * sounds.len();
+
+    let mut samples = Vec::with_capacity(total);
+    let mut phase = 0.0f32;
+    for n in 0..total {
+        let mora = n / mora_samples;
+        let offset = n % mora_samples;
+
+        // Glide from the previous mora's pitch into this one's
+        let target = targets[mora];
+        let previous = if mora == 0 { target } else { targets[mora - 1] };
+        let f0 = if offset < glide_samples {
+            previous + (target - previous) * offset as f32 / glide_samples as f32
+        } else {
+            target
+        };
+        phase = (phase + f0 / rate).fract();
+
+        let value = match sounds[mora] {
+            Sound::Silence => 0.0,
+            Sound::Vowel(formants) => {
+                // Fade at the edges of voiced stretches only
+                let voiced_before = mora > 0 && sounds[mora - 1] != Sound::Silence;
+                let voiced_after = mora + 1 < sounds.len() && sounds[mora + 1] != Sound::Silence;
+                let mut gain = 1.0f32;
+                if !voiced_before {
+                    gain = gain.min(offset as f32 / fade_samples);
+                }
+                if !voiced_after {
+                    gain = gain.min((mora_samples - offset) as f32 / fade_samples);
+                }
+                gain * voice(phase, f0, formants)
+            }
+        };
+        samples.push((value.clamp(-1.0, 1.0) * 0.8 * i16::MAX as f32) as i16);
+    }
+    samples
+}
+
+/// One sample of a harmonic tone, harmonics weighted by closeness to the formants
+fn voice(phase: f32, f0: f32, formants: [f32; 2]) -> f32 {
+    const BANDWIDTH: f32 = 120.0;
+    let mut value = 0.0;
+    let mut norm = 0.0;
+    let mut harmonic = 1;
+    while harmonic as f32 * f0 < 4000.0 {
+        let frequency = harmonic as f32 * f0;
+        let weight: f32 = formants.iter()
+            .map(|&f| 1.0 / (1.0 + ((frequency - f) / BANDWIDTH).powi(2)))
+            .sum::<f32>()
+            + 0.05 / harmonic as f32;
+        value += weight * (2.0 * PI * harmonic as f32 * phase).sin();
+        norm += weight;
+        harmonic += 1;
+    }
+    if norm > 0.0 { value / norm } else { 0.0 }
+}
+
+/// A WAV file (PCM, mono, 16-bit) of `reading` said with an NHK accent number
+pub fn wav_bytes(reading: &str, accent: u8, options: &SynthesisOptions) -> Vec<u8> {
+    encode_wav(&synthesize(reading, accent, options), options.sample_rate)
+}
+
+/// Write [`wav_bytes`] to a file
+pub fn write_wav(path: impl AsRef<Path>, reading: &str, accent: u8, options: &SynthesisOptions) -> std::io::Result<()> {
+    std::fs::write(path, wav_bytes(reading, accent, options))
+}
+
+/// Audio of the searched word against each homophone with a different pitch
+///
+/// Uses each word's first accent. Empty unless the result is a
+/// [`FindWithNhkResult::UniqueMatch`] with `different_pitch_homophones`.
+pub fn pitch_contrasts(result: &FindWithNhkResult, options: &SynthesisOptions) -> Vec<PitchContrast> {
+    let FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } = result else {
+        return Vec::new();
+    };
+    let Some(target) = true_homophones.iter()
+        .find(|w| w.match_kind != MatchKind::SharedReading && !w.pitch_accent.is_empty())
+        .or_else(|| true_homophones.iter().find(|w| !w.pitch_accent.is_empty()))
+    else {
+        return Vec::new();
+    };
+
+    let pause = vec![0i16; (options.sample_rate * PAUSE_MILLISECONDS / 1000) as usize];
+    different_pitch_homophones.iter()
+        .filter(|w| !w.pitch_accent.is_empty())
+        .map(|other| {
+            let mut samples = synthesize(&target.reading, target.pitch_accent[0], options);
+            samples.extend(&pause);
+            samples.extend(synthesize(&other.reading, other.pitch_accent[0], options));
+            PitchContrast {
+                first: target.clone(),
+                second: other.clone(),
+                wav: encode_wav(&samples, options.sample_rate),
+            }
+        })
+        .collect()
+}
+
+fn encode_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contour() {
+        let options = SynthesisOptions::default();
+        // 橋[2]: low, high, then a low particle; 箸[1]: high, low, low
+        assert_eq!(contour("はし", 2, &options), vec![120.0, 170.0, 120.0]);
+        assert_eq!(contour("はし", 1, &options), vec![170.0, 120.0, 120.0]);
+        let without_particle = SynthesisOptions { particle: false, ..options };
+        assert_eq!(contour("はし", 2, &without_particle).len(), 2);
+    }
+
+    #[test]
+    fn test_wav() {
+        let options = SynthesisOptions::default();
+        let wav = wav_bytes("はし", 2, &options);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        let samples = (options.sample_rate * options.mora_milliseconds / 1000) as usize * 3;
+        assert_eq!(wav.len(), 44 + samples * 2);
+
+        // Deterministic, and the accent changes the audio
+        assert_eq!(wav, wav_bytes("はし", 2, &options));
+        assert_ne!(wav, wav_bytes("はし", 1, &options));
+
+        // っ is silent
+        let gakkou = synthesize("がっこう", 0, &options);
+        let mora = gakkou.len() / 5;
+        assert!(gakkou[mora..2 * mora].iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_pitch_contrasts() {
+        let result = crate::find_with_nhk("橋");
+        let contrasts = pitch_contrasts(&result, &SynthesisOptions::default());
+        let chopsticks = contrasts.iter().find(|c| c.second.text == "箸").unwrap();
+        assert_eq!(chopsticks.first.text, "橋");
+        assert_eq!(&chopsticks.wav[0..4], b"RIFF");
+    }
+}
@@ -0,0 +1,185 @@
+//! Compounds JMDict does not list as a unit, such as 公正取引.
+//!
+//! A compound is split into JMDict words by longest match from the left, then
+//! each word is swapped for its homophones to list the compounds a reader
+//! could hear instead (構成取引, 校正取引...).
+
+use crate::{MatchKind, WordFrequency};
+use std::collections::{HashMap, HashSet};
+
+/// One dictionary word of a compound
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompoundPart {
+    pub text: String,
+    /// The word once per JMDict reading, in JMDict order
+    pub readings: Vec<WordFrequency>,
+}
+
+/// A compound made of one word (and reading) per part of the input
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompoundAlternative {
+    pub text: String,
+    /// Readings of the parts joined, without rendaku
+    pub reading: String,
+    /// Swapped parts have [`MatchKind::SharedReading`]
+    pub parts: Vec<WordFrequency>,
+    /// Sum of the parts' frequency scores
+    pub frequency_score: u32,
+}
+
+/// Split a compound into JMDict words, longest match first from the left
+///
+/// Words match by kanji spelling, or by reading for kana-only words. Returns
+/// `None` if some part of the input is no JMDict word.
+pub fn split_compound(text: &str) -> Option<Vec<CompoundPart>> {
+    let boundaries: Vec<usize> = text.char_indices().map(|(i, _)| i).chain(std::iter::once(text.len())).collect();
+    let substrings: HashSet<&str> = boundaries.iter()
+        .enumerate()
+        .flat_map(|(n, &start)| boundaries[n + 1..].iter().map(move |&end| &text[start..end]))
+        .collect();
+    if substrings.is_empty() {
+        return None;
+    }
+
+    let mut words: HashMap<&str, Vec<WordFrequency>> = HashMap::new();
+    for entry in jmdict::entries() {
+        if entry.kanji_elements().count() == 0 {
+            for reading in entry.reading_elements().filter(|r| substrings.contains(r.text)) {
+                words.entry(reading.text).or_default()
+                    .push(WordFrequency::jmdict(entry.number, reading.text, reading.text, &reading.priority, MatchKind::Reading));
+            }
+            continue;
+        }
+        for kanji in entry.kanji_elements().filter(|k| substrings.contains(k.text)) {
+            for reading in entry.reading_elements() {
+                words.entry(kanji.text).or_default()
+                    .push(WordFrequency::jmdict(entry.number, kanji.text, reading.text, &kanji.priority, MatchKind::KanjiElement));
+            }
+        }
+    }
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    while start + 1 < boundaries.len() {
+        let (end, readings) = (start + 1..boundaries.len()).rev()
+            .find_map(|end| words.get(&text[boundaries[start]..boundaries[end]]).map(|r| (end, r.clone())))?;
+        parts.push(CompoundPart {
+            text: text[boundaries[start]..boundaries[end]].to_string(),
+            readings,
+        });
+        start = end;
+    }
+    Some(parts)
+}
+
+/// The input and the compounds made by swapping its parts for homophones
+///
+/// 公正取引 gives 公正取引, 構成取引, 校正取引... Every reading of every part
+/// is used, so the input may come back under several readings. The `limit`
+/// most frequent compounds are returned, ranked by the sum of their parts'
+/// frequency scores. Empty if the input does not split, see [`split_compound`].
+pub fn compound_homophones(text: &str, limit: usize) -> Vec<CompoundAlternative> {
+    let Some(parts) = split_compound(text) else {
+        return Vec::new();
+    };
+
+    let readings: HashSet<&str> = parts.iter()
+        .flat_map(|p| p.readings.iter().map(|w| w.reading.as_str()))
+        .collect();
+    let mut homophones: HashMap<&str, Vec<WordFrequency>> = HashMap::new();
+    for entry in jmdict::entries() {
+        for reading in entry.reading_elements().filter(|r| readings.contains(r.text)) {
+            let words = homophones.entry(reading.text).or_default();
+            if entry.kanji_elements().count() == 0 {
+                words.push(WordFrequency::jmdict(entry.number, reading.text, reading.text, &reading.priority, MatchKind::SharedReading));
+            }
+            for kanji in entry.kanji_elements() {
+                words.push(WordFrequency::jmdict(entry.number, kanji.text, reading.text, &kanji.priority, MatchKind::SharedReading));
+            }
+        }
+    }
+
+    // Each part's own readings, then the words sharing them, most frequent first
+    let options: Vec<Vec<WordFrequency>> = parts.iter()
+        .map(|part| {
+            let mut seen = HashSet::new();
+            let mut words: Vec<WordFrequency> = part.readings.iter()
+                .cloned()
+                .chain(part.readings.iter().flat_map(|r| homophones.get(r.reading.as_str()).into_iter().flatten().cloned()))
+                .filter(|w| seen.insert((w.text.clone(), w.reading.clone())))
+                .collect();
+            words.sort_by_key(|w| std::cmp::Reverse(w.frequency_score));
+            words.truncate(limit);
+            words
+        })
+        .collect();
+
+    // Scores add up, so the best `limit` compounds only ever extend the best
+    // `limit` compounds of the parts before them
+    let mut compounds: Vec<(u32, Vec<&WordFrequency>)> = vec![(0, Vec::new())];
+    for words in &options {
+        let mut extended: Vec<(u32, Vec<&WordFrequency>)> = compounds.iter()
+            .flat_map(|(score, chosen)| words.iter().map(move |w| {
+                let mut chosen = chosen.clone();
+                chosen.push(w);
+                (score + w.frequency_score, chosen)
+            }))
+            .collect();
+        extended.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        extended.truncate(limit);
+        compounds = extended;
+    }
+
+    compounds.into_iter()
+        .filter(|(_, chosen)| !chosen.is_empty())
+        .map(|(frequency_score, chosen)| CompoundAlternative {
+            text: chosen.iter().map(|w| w.text.as_str()).collect(),
+            reading: chosen.iter().map(|w| w.reading.as_str()).collect(),
+            parts: chosen.into_iter().cloned().collect(),
+            frequency_score,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_compound() {
+        let parts = split_compound("公正取引").unwrap();
+        let texts: Vec<&str> = parts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, vec!["公正", "取引"]);
+        assert_eq!(parts[0].readings[0].reading, "こうせい");
+
+        // Multi-kanji words are kept whole
+        assert_eq!(split_compound("中国語取引").unwrap()[0].text, "中国語");
+        assert_eq!(split_compound("取引取引").unwrap().len(), 2);
+        assert_eq!(split_compound("公正ほげ"), None);
+        assert_eq!(split_compound(""), None);
+    }
+
+    #[test]
+    fn test_compound_homophones() {
+        let alternatives = compound_homophones("公正取引", 20);
+        let texts: Vec<&str> = alternatives.iter().map(|a| a.text.as_str()).collect();
+        for text in ["公正取引", "構成取引", "校正取引", "厚生取引"] {
+            assert!(texts.contains(&text), "{text}");
+        }
+        assert!(alternatives.iter().all(|a| a.reading == "こうせいとりひき"));
+        for i in 1..alternatives.len() {
+            assert!(alternatives[i - 1].frequency_score >= alternatives[i].frequency_score);
+        }
+
+        let input = alternatives.iter().find(|a| a.text == "公正取引").unwrap();
+        assert!(input.parts.iter().all(|p| p.match_kind == MatchKind::KanjiElement));
+        let swapped = alternatives.iter().find(|a| a.text == "構成取引").unwrap();
+        assert_eq!(swapped.parts[0].match_kind, MatchKind::SharedReading);
+        assert_eq!(swapped.frequency_score, swapped.parts.iter().map(|p| p.frequency_score).sum::<u32>());
+
+        assert_eq!(compound_homophones("公正取引", 3).len(), 3);
+        assert!(compound_homophones("ほげ", 10).is_empty());
+    }
+}
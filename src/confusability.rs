@@ -0,0 +1,236 @@
+//! How likely a listener is to mistake a homophone for the input.
+//!
+//! A common word pitched the same way is the likeliest confusion, a rare or
+//! differently pitched one the least. Odaka and heiban words sound the same
+//! on their own and only part when a particle follows (橋[2] and 端[0]), so they
+//! count as nearly identical.
+
+use crate::pitch::{AccentType, accent_type, mora_count, pitch_pattern};
+use crate::{FindWithNhkResult, MatchKind, WordFrequencyWithPitch};
+use crate::filter::entry_parts_of_speech;
+use std::collections::HashMap;
+
+/// Highest score [`crate::calculate_frequency_score`] gives
+const MAX_FREQUENCY_SCORE: f64 = 50_700.0;
+
+/// Confusability of a homophone and what it is made of, each from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Confusability {
+    /// Product of the parts below
+    pub score: f64,
+    /// Frequency of the homophone; rare words keep a floor of 0.1
+    pub frequency: f64,
+    /// Pitch overlap with the input, see [`pitch_overlap`]
+    pub pitch: f64,
+    /// 1 if the words share a [`crate::PartOfSpeech`], 0.5 if not,
+    /// `None` unless asked for
+    pub part_of_speech: Option<f64>,
+}
+
+/// What [`Confusability`] takes into account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfusabilityOptions {
+    /// Rank words of another part of speech lower: a noun is seldom heard
+    /// where a verb is expected
+    pub part_of_speech: bool,
+}
+
+/// A homophone with its confusability
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConfusableWord {
+    pub word: WordFrequencyWithPitch,
+    pub confusability: Confusability,
+}
+
+/// Pitch similarity of two words, from 0 to 1
+///
+/// 1 if they share an accent or either accent is unknown (as
+/// [`crate::find_with_nhk`] counts such words as true homophones), 0.75 for
+/// odaka against heiban, otherwise half the share of morae, and the following
+/// particle, pitched alike.
+pub fn pitch_overlap(reading: &str, accents: &[u8], other_reading: &str, other_accents: &[u8]) -> f64 {
+    if accents.is_empty() || other_accents.is_empty() {
+        return 1.0;
+    }
+    let morae = mora_count(reading);
+    let mut best: f64 = 0.0;
+    for &a in accents {
+        for &b in other_accents {
+            let overlap = if a == b && morae == mora_count(other_reading) {
+                1.0
+            } else if morae == mora_count(other_reading)
+                && matches!(
+                    (accent_type(a, morae), accent_type(b, morae)),
+                    (AccentType::Odaka, AccentType::Heiban) | (AccentType::Heiban, AccentType::Odaka)
+                )
+            {
+                0.75
+            } else {
+                let first = pitch_pattern(reading, a);
+                let second = pitch_pattern(other_reading, b);
+                let alike = first.iter().zip(&second).filter(|(x, y)| x == y).count();
+                0.5 * alike as f64 / first.len().max(second.len()) as f64
+            };
+            best = best.max(overlap);
+        }
+    }
+    best
+}
+
+fn frequency(word: &WordFrequencyWithPitch) -> f64 {
+    0.1 + 0.9 * (word.frequency_score as f64 / MAX_FREQUENCY_SCORE).min(1.0)
+}
+
+/// Score each word against the target, or against the other words without one
+fn score(
+    words: &[WordFrequencyWithPitch],
+    target: Option<&WordFrequencyWithPitch>,
+    options: &ConfusabilityOptions,
+) -> Vec<Confusability> {
+    let categories = if options.part_of_speech {
+        let entries = words.iter().chain(target).filter_map(|w| w.jmdict_entry).collect();
+        entry_parts_of_speech(&entries)
+    } else {
+        HashMap::new()
+    };
+    let categories_of = |word: &WordFrequencyWithPitch| word.jmdict_entry.and_then(|n| categories.get(&n));
+
+    words.iter()
+        .map(|word| {
+            let others: Vec<&WordFrequencyWithPitch> = match target {
+                Some(target) => vec![target],
+                None => words.iter().filter(|w| !same_word(w, word)).collect(),
+            };
+            let pitch = others.iter()
+                .map(|other| pitch_overlap(&other.reading, &other.pitch_accent, &word.reading, &word.pitch_accent))
+                .fold(0.0, f64::max);
+            let part_of_speech = options.part_of_speech.then(|| {
+                let shared = others.iter().any(|other| match (categories_of(other), categories_of(word)) {
+                    (Some(a), Some(b)) => !a.is_disjoint(b),
+                    // Names, user words: nothing to compare
+                    _ => true,
+                });
+                if shared { 1.0 } else { 0.5 }
+            });
+            let frequency = frequency(word);
+            Confusability { score: frequency * pitch * part_of_speech.unwrap_or(1.0), frequency, pitch, part_of_speech }
+        })
+        .collect()
+}
+
+fn same_word(a: &WordFrequencyWithPitch, b: &WordFrequencyWithPitch) -> bool {
+    match (a.jmdict_entry, b.jmdict_entry) {
+        (Some(x), Some(y)) => x == y,
+        _ => a.text == b.text,
+    }
+}
+
+/// The word the input spells, among a [`FindWithNhkResult::UniqueMatch`]'s true homophones
+pub(crate) fn input_word(true_homophones: &[WordFrequencyWithPitch]) -> Option<&WordFrequencyWithPitch> {
+    true_homophones.iter().find(|w| w.match_kind != MatchKind::SharedReading)
+}
+
+/// Sort words most confusable first; ties keep their order
+pub(crate) fn sort_by_confusability(
+    words: &mut Vec<WordFrequencyWithPitch>,
+    target: Option<&WordFrequencyWithPitch>,
+    options: &ConfusabilityOptions,
+) {
+    let scores = score(words, target, options);
+    let mut scored: Vec<(f64, WordFrequencyWithPitch)> = scores.into_iter().map(|c| c.score).zip(words.drain(..)).collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    words.extend(scored.into_iter().map(|(_, word)| word));
+}
+
+impl FindWithNhkResult {
+    /// Homophones of the input, most confusable first
+    ///
+    /// For a word the input spells, each other word is compared with it, and
+    /// the input's own entry is left out. For a reading, each word is compared
+    /// with its closest-sounding neighbour in the list.
+    pub fn by_confusability(&self, options: &ConfusabilityOptions) -> Vec<ConfusableWord> {
+        let (words, target): (Vec<WordFrequencyWithPitch>, Option<WordFrequencyWithPitch>) = match self {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
+                let Some(target) = input_word(true_homophones) else {
+                    return Vec::new();
+                };
+                let words = true_homophones.iter()
+                    .chain(different_pitch_homophones)
+                    .filter(|w| !same_word(w, target))
+                    .cloned()
+                    .collect();
+                (words, Some(target.clone()))
+            }
+            FindWithNhkResult::MultipleMatches { homophones, .. } => (homophones.clone(), None),
+            FindWithNhkResult::NotFound | FindWithNhkResult::NoHomophones | FindWithNhkResult::Affix { .. } => {
+                return Vec::new()
+            }
+        };
+
+        let scores = score(&words, target.as_ref(), options);
+        let mut ranked: Vec<ConfusableWord> = words.into_iter()
+            .zip(scores)
+            .map(|(word, confusability)| ConfusableWord { word, confusability })
+            .collect();
+        ranked.sort_by(|a, b| b.confusability.score.total_cmp(&a.confusability.score));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FindOptions, find_with_nhk, find_with_nhk_options};
+
+    #[test]
+    fn test_pitch_overlap() {
+        assert_eq!(pitch_overlap("はし", &[2], "はし", &[2]), 1.0);
+        // 橋 and 端 differ only on the particle
+        assert_eq!(pitch_overlap("はし", &[2], "はし", &[0]), 0.75);
+        // 箸 HL(L) against 橋 LH(L): only the particle agrees
+        let chopsticks = pitch_overlap("はし", &[1], "はし", &[2]);
+        assert!(chopsticks > 0.0 && chopsticks < 0.75);
+        assert_eq!(pitch_overlap("はし", &[1], "はし", &[]), 1.0);
+        assert_eq!(pitch_overlap("はし", &[1, 2], "はし", &[2]), 1.0);
+    }
+
+    #[test]
+    fn test_by_confusability() {
+        let ranked = find_with_nhk("橋").by_confusability(&ConfusabilityOptions::default());
+        assert!(ranked.iter().all(|w| w.word.text != "橋"));
+        for i in 1..ranked.len() {
+            assert!(ranked[i - 1].confusability.score >= ranked[i].confusability.score);
+        }
+        let end = ranked.iter().find(|w| w.word.text == "端").unwrap();
+        assert_eq!(end.confusability.pitch, 0.75);
+        let chopsticks = ranked.iter().find(|w| w.word.text == "箸").unwrap();
+        assert!(chopsticks.confusability.pitch < end.confusability.pitch);
+
+        // 買う and 飼う are both verbs
+        let options = ConfusabilityOptions { part_of_speech: true };
+        let ranked = find_with_nhk("買う").by_confusability(&options);
+        let keep = ranked.iter().find(|w| w.word.text == "飼う").unwrap();
+        assert_eq!(keep.confusability.part_of_speech, Some(1.0));
+
+        let ranked = find_with_nhk("はし").by_confusability(&ConfusabilityOptions::default());
+        assert!(ranked.iter().any(|w| w.word.text == "橋"));
+    }
+
+    #[test]
+    fn test_sort_option() {
+        let options = FindOptions { confusability: Some(ConfusabilityOptions::default()), ..Default::default() };
+        let FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } = find_with_nhk_options("橋", &options) else {
+            panic!("expected a unique match for 橋");
+        };
+        let ranked = find_with_nhk("橋").by_confusability(&ConfusabilityOptions::default());
+        let expected: Vec<&str> = ranked.iter()
+            .filter(|w| different_pitch_homophones.iter().any(|d| d.text == w.word.text))
+            .map(|w| w.word.text.as_str())
+            .collect();
+        let sorted: Vec<&str> = different_pitch_homophones.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(sorted, expected);
+        assert_eq!(true_homophones[0].text, "橋");
+    }
+}
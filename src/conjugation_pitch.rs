@@ -0,0 +1,231 @@
+//! Pitch accent of conjugated verbs and i-adjectives.
+//!
+//! NHK gives the accent of dictionary forms only. Conjugated forms follow
+//! from whether the word is accented, by the usual Tokyo rules:
+//!
+//! - Unaccented (heiban) verbs stay heiban in 〜た, 〜て and 〜ない (買った,
+//!   買わない), accented verbs keep or move back their downstep (飼った か'った,
+//!   食べた た'べた) and fall before 〜ない (飼わない かわ'ない).
+//! - 〜ます is always accented on ま (かいま'す), 〜ません on せ, 〜たい on た.
+//! - Unaccented i-adjectives fall before the ending (赤かった あか'かった),
+//!   accented ones one mora before their downstep (高かった た'かかった).
+//!
+//! A downstep made by one suffix stays where it is under the next (かわ'なかった).
+
+use crate::deinflect::{
+    ADJECTIVE, ConjugationClass, GODAN, Inflection, MASU, TE, apply_rules, conjugation_rules, rules,
+};
+use crate::pitch::mora_count;
+
+/// Whether a word has a downstep in its dictionary form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AccentClass {
+    /// Heiban: 買う[0], 赤い[0]
+    Unaccented,
+    /// 飼う[1], 高い[2]
+    Accented,
+}
+
+impl AccentClass {
+    pub fn of(accent: u8) -> AccentClass {
+        if accent == 0 { AccentClass::Unaccented } else { AccentClass::Accented }
+    }
+
+    /// Usual accent number of a dictionary form of this class: accented verbs
+    /// and adjectives fall after the penultimate mora (たべ'る, たか'い)
+    pub fn dictionary_accent(self, reading: &str) -> u8 {
+        match self {
+            AccentClass::Unaccented => 0,
+            AccentClass::Accented => mora_count(reading).saturating_sub(1).max(1) as u8,
+        }
+    }
+}
+
+/// A conjugated form with its accent
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConjugatedAccent {
+    pub inflections: Vec<Inflection>,
+    /// Reading of the conjugated form (hiragana)
+    pub reading: String,
+    /// NHK accent number of the conjugated form
+    pub accent: u8,
+}
+
+/// Forms listed by [`conjugation_accents`]
+const STANDARD_FORMS: &[&[Inflection]] = {
+    use Inflection::*;
+    &[
+        &[Polite],
+        &[Polite, Past],
+        &[Polite, Negative],
+        &[Past],
+        &[Te],
+        &[Negative],
+        &[Negative, Past],
+        &[Volitional],
+        &[Desire],
+        &[Conditional],
+        &[Tara],
+        &[Imperative],
+        &[Potential],
+        &[Passive],
+        &[Causative],
+        &[Adverbial],
+    ]
+};
+
+/// Accent of `reading` conjugated with `inflections`, given the accent of the dictionary form
+///
+/// `reading` is the kana dictionary form (かう, こうせいする for a noun taking
+/// する). Returns `None` where [`crate::deinflect::conjugate`] does.
+pub fn conjugated_accent(
+    reading: &str,
+    class: ConjugationClass,
+    accent: u8,
+    inflections: &[Inflection],
+) -> Option<ConjugatedAccent> {
+    let chain = conjugation_rules(reading, class, inflections)?;
+    Some(ConjugatedAccent {
+        inflections: inflections.to_vec(),
+        reading: apply_rules(reading, &chain)?,
+        accent: accent_after_rules(reading, class, accent, &chain)?,
+    })
+}
+
+/// Accents of the standard conjugations of a verb or i-adjective
+///
+/// Forms the class does not have (〜ます of adjectives, adverbial 〜く of
+/// verbs) are left out.
+pub fn conjugation_accents(reading: &str, class: ConjugationClass, accent: u8) -> Vec<ConjugatedAccent> {
+    STANDARD_FORMS.iter()
+        .filter_map(|inflections| conjugated_accent(reading, class, accent, inflections))
+        .collect()
+}
+
+/// Accent after applying rules of [`crate::deinflect`] to a dictionary form
+pub(crate) fn accent_after_rules(reading: &str, class: ConjugationClass, accent: u8, chain: &[usize]) -> Option<u8> {
+    let mut text = reading.to_string();
+    let mut accent = accent;
+    // The downstep of a noun taking する belongs to the noun
+    let mut own = accent > 0 && class != ConjugationClass::Suru;
+    let mut word_type = class.word_type();
+
+    for (step, &i) in chain.iter().enumerate() {
+        let rule = &rules()[i];
+        let before = mora_count(&text);
+        text = format!("{}{}", text.strip_suffix(rule.base.as_str())?, rule.inflected);
+        // A derived verb or adjective is accented like a dictionary form
+        // when its downstep is on the penultimate mora (かわれ'る, かいた'い)
+        if step > 0 {
+            own = accent > 0 && accent as usize + 1 == before;
+        }
+        if accent == 0 || own {
+            accent = step_accent(word_type, accent, before, mora_count(&text), rule.inflections[0]);
+        }
+        word_type = rule.inflected_type;
+    }
+    Some(accent)
+}
+
+/// Accent after one suffix, for a word of `word_type` that is heiban
+/// (`accent` 0) or accented on its own nucleus
+fn step_accent(word_type: u8, accent: u8, before: usize, after: usize, inflection: Inflection) -> u8 {
+    use Inflection::*;
+
+    let heiban = accent == 0;
+    let accent = accent as usize;
+    let result = match word_type {
+        // ま of 〜ます, せ of 〜ません, しょ of 〜ましょう
+        MASU => match inflection {
+            Negative | Volitional => before,
+            _ => before - 1,
+        },
+        ADJECTIVE if heiban => match inflection {
+            Adverbial => 0,
+            // あかくな'い
+            Negative => before + 1,
+            // あか'かった, あか'くて, あか'ければ
+            _ => before - 1,
+        },
+        ADJECTIVE => accent.saturating_sub(1).max(1),
+        TE => accent,
+        // Verbs
+        _ => match inflection {
+            Polite | Volitional | Desire => after - 1,
+            Conditional if heiban => after - 1,
+            Imperative if heiban => after,
+            Conditional | Imperative => accent,
+            _ if heiban => 0,
+            Past | Te | Tara if word_type == GODAN => accent,
+            Past | Te | Tara => accent.saturating_sub(1).max(1),
+            Negative => after - 2,
+            Potential | Passive | Causative => after - 1,
+            _ => accent,
+        },
+    };
+    result as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConjugationClass::*;
+    use Inflection::*;
+
+    fn accent(reading: &str, class: ConjugationClass, accent: u8, inflections: &[Inflection]) -> (String, u8) {
+        let form = conjugated_accent(reading, class, accent, inflections).unwrap();
+        (form.reading, form.accent)
+    }
+
+    #[test]
+    fn test_verbs() {
+        // 買う[0] and 飼う[1]
+        assert_eq!(accent("かう", Godan, 0, &[Polite]), ("かいます".to_string(), 3));
+        assert_eq!(accent("かう", Godan, 0, &[Past]), ("かった".to_string(), 0));
+        assert_eq!(accent("かう", Godan, 0, &[Negative]), ("かわない".to_string(), 0));
+        assert_eq!(accent("かう", Godan, 0, &[Negative, Past]).1, 3);
+        assert_eq!(accent("かう", Godan, 0, &[Volitional]).1, 2);
+        assert_eq!(accent("かう", Godan, 1, &[Polite]).1, 3);
+        assert_eq!(accent("かう", Godan, 1, &[Past]).1, 1);
+        assert_eq!(accent("かう", Godan, 1, &[Negative]).1, 2);
+        assert_eq!(accent("かう", Godan, 1, &[Negative, Past]).1, 2);
+        assert_eq!(accent("かう", Godan, 1, &[Passive, Past]), ("かわれた".to_string(), 2));
+
+        // Ichidan verbs move the downstep back in 〜た
+        assert_eq!(accent("たべる", Ichidan, 2, &[Past]).1, 1);
+        assert_eq!(accent("たべる", Ichidan, 2, &[Negative]).1, 2);
+        assert_eq!(accent("みる", Ichidan, 1, &[Past]).1, 1);
+        assert_eq!(accent("くる", Kuru, 1, &[Negative]), ("こない".to_string(), 1));
+
+        // Polite forms
+        assert_eq!(accent("かう", Godan, 0, &[Polite, Negative]).1, 4);
+        assert_eq!(accent("かう", Godan, 0, &[Polite, Past]).1, 3);
+        assert_eq!(accent("かう", Godan, 1, &[Te, Progressive, Polite]).1, 1);
+
+        // The noun keeps its downstep before する
+        assert_eq!(accent("こうせいする", Suru, 0, &[Past]).1, 0);
+        assert_eq!(accent("こうせいする", Suru, 0, &[Volitional]).1, 6);
+        assert_eq!(accent("ごせする", Suru, 1, &[Past]).1, 1);
+    }
+
+    #[test]
+    fn test_adjectives() {
+        assert_eq!(accent("あかい", IAdjective, 0, &[Past]), ("あかかった".to_string(), 2));
+        assert_eq!(accent("あかい", IAdjective, 0, &[Negative]).1, 4);
+        assert_eq!(accent("あかい", IAdjective, 0, &[Adverbial]).1, 0);
+        assert_eq!(accent("たかい", IAdjective, 2, &[Past]).1, 1);
+        assert_eq!(accent("たかい", IAdjective, 2, &[Negative]).1, 1);
+        assert_eq!(accent("いい", IAdjective, 1, &[Past]), ("よかった".to_string(), 1));
+        // かわいい only looks like a compound of いい
+        assert_eq!(accent("かわいい", IAdjective, 3, &[Past]), ("かわいかった".to_string(), 2));
+        assert_eq!(accent("かわいい", IAdjective, 3, &[Negative]), ("かわいくない".to_string(), 2));
+        assert!(conjugation_accents("かわいい", IAdjective, 3).iter().all(|f| !f.reading.contains('よ')));
+
+        let forms = conjugation_accents("たかい", IAdjective, AccentClass::Accented.dictionary_accent("たかい"));
+        assert!(forms.iter().all(|f| f.accent == 1));
+        assert!(forms.iter().all(|f| !f.inflections.contains(&Polite)));
+        assert_eq!(AccentClass::of(0), AccentClass::Unaccented);
+    }
+}
@@ -0,0 +1,902 @@
+//! Deinflection of conjugated verbs and i-adjectives.
+//!
+//! JMDict only lists dictionary forms, so 買った is looked up by undoing one
+//! suffix at a time (買った → 買う) until a dictionary form is left. Candidates
+//! are only kept if JMDict has the word with a matching part-of-speech tag:
+//! `v5u` for 買う, `v1` for 食べる, `vk` for 来る, `vs` for nouns taking する,
+//! `adj-i` for 高い.
+
+use crate::conjugation_pitch::accent_after_rules;
+use crate::kana_utils::katakana_to_hiragana;
+use crate::{
+    FindWithNhkResult, MatchKind, PitchSource, WordFrequency, WordFrequencyWithPitch, distinct_word_count,
+    reading_match_kind,
+};
+use jmdict::Enum;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// One conjugation step, from the dictionary form outward
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Inflection {
+    /// 〜た
+    Past,
+    /// 〜て
+    Te,
+    /// 〜ない
+    Negative,
+    /// 〜ます
+    Polite,
+    /// 〜う, 〜よう
+    Volitional,
+    /// 〜える, 〜られる
+    Potential,
+    /// 〜れる, 〜られる
+    Passive,
+    /// 〜せる, 〜させる
+    Causative,
+    /// 〜え, 〜ろ
+    Imperative,
+    /// 〜ば
+    Conditional,
+    /// 〜たら
+    Tara,
+    /// 〜たい
+    Desire,
+    /// 〜ている, 〜てる
+    Progressive,
+    /// 〜てしまう, 〜ちゃう
+    Completion,
+    /// Adverbial 〜く of i-adjectives
+    Adverbial,
+}
+
+/// Conjugation class of a dictionary form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConjugationClass {
+    /// 五段 verbs: 買う, 書く, 刈る
+    Godan,
+    /// 一段 verbs: 食べる, 見る
+    Ichidan,
+    /// 来る
+    Kuru,
+    /// する, and nouns taking する
+    Suru,
+    /// i-adjectives: 高い
+    IAdjective,
+}
+
+// Word types a suffix attaches to or produces. Besides the dictionary
+// classes, polite 〜ます forms, 〜て forms and final forms that take no
+// further suffix.
+pub(crate) const ICHIDAN: u8 = 1;
+pub(crate) const GODAN: u8 = 1 << 1;
+pub(crate) const KURU: u8 = 1 << 2;
+pub(crate) const SURU: u8 = 1 << 3;
+pub(crate) const ADJECTIVE: u8 = 1 << 4;
+pub(crate) const MASU: u8 = 1 << 5;
+pub(crate) const TE: u8 = 1 << 6;
+pub(crate) const FINAL: u8 = 1 << 7;
+
+/// Longest inflection chain tried, 食べさせられなかった has 4
+const MAX_INFLECTIONS: usize = 6;
+
+impl ConjugationClass {
+    pub(crate) fn word_type(self) -> u8 {
+        match self {
+            ConjugationClass::Ichidan => ICHIDAN,
+            ConjugationClass::Godan => GODAN,
+            ConjugationClass::Kuru => KURU,
+            ConjugationClass::Suru => SURU,
+            ConjugationClass::IAdjective => ADJECTIVE,
+        }
+    }
+
+    fn from_word_type(word_type: u8) -> Option<ConjugationClass> {
+        match word_type {
+            ICHIDAN => Some(ConjugationClass::Ichidan),
+            GODAN => Some(ConjugationClass::Godan),
+            KURU => Some(ConjugationClass::Kuru),
+            SURU => Some(ConjugationClass::Suru),
+            ADJECTIVE => Some(ConjugationClass::IAdjective),
+            _ => None,
+        }
+    }
+
+    /// Whether a JMDict part-of-speech code belongs to this class
+    ///
+    /// `suru_noun` asks for a noun taking する (`vs`) rather than a する verb.
+    fn matches(self, code: &str, lemma: &str, suru_noun: bool) -> bool {
+        match self {
+            ConjugationClass::Ichidan => code == "v1" || code == "v1-s",
+            ConjugationClass::Godan => {
+                let row = lemma.chars().last().and_then(godan_row);
+                code.strip_prefix("v5").is_some_and(|rest| row.is_some_and(|r| rest.starts_with(r)))
+            }
+            ConjugationClass::Kuru => code == "vk",
+            ConjugationClass::Suru if suru_noun => code == "vs",
+            ConjugationClass::Suru => code == "vs-i" || code == "vs-s",
+            ConjugationClass::IAdjective => code == "adj-i" || code == "adj-ix",
+        }
+    }
+}
+
+/// Letter of the `v5` code for a godan verb ending
+fn godan_row(ending: char) -> Option<char> {
+    match ending {
+        'う' => Some('u'),
+        'く' => Some('k'),
+        'ぐ' => Some('g'),
+        'す' => Some('s'),
+        'つ' => Some('t'),
+        'ぬ' => Some('n'),
+        'ぶ' => Some('b'),
+        'む' => Some('m'),
+        'る' => Some('r'),
+        _ => None,
+    }
+}
+
+/// Replace the `base` ending of a `base_type` word with `inflected`
+#[derive(Debug)]
+pub(crate) struct Rule {
+    pub(crate) inflected: String,
+    pub(crate) base: String,
+    pub(crate) inflected_type: u8,
+    pub(crate) base_type: u8,
+    pub(crate) inflections: &'static [Inflection],
+    /// Only for verbs read いく or ゆく, see [`is_iku_verb`]
+    pub(crate) iku: bool,
+    /// Only for いい and its compounds, see [`is_ii_adjective`]
+    pub(crate) yoi: bool,
+}
+
+/// Godan endings: dictionary, 〜ます stem, 〜ない stem, 〜ば stem, volitional stem, 〜て, 〜た
+const GODAN_ROWS: [[&str; 7]; 9] = [
+    ["う", "い", "わ", "え", "お", "って", "った"],
+    ["く", "き", "か", "け", "こ", "いて", "いた"],
+    ["ぐ", "ぎ", "が", "げ", "ご", "いで", "いだ"],
+    ["す", "し", "さ", "せ", "そ", "して", "した"],
+    ["つ", "ち", "た", "て", "と", "って", "った"],
+    ["ぬ", "に", "な", "ね", "の", "んで", "んだ"],
+    ["ぶ", "び", "ば", "べ", "ぼ", "んで", "んだ"],
+    ["む", "み", "ま", "め", "も", "んで", "んだ"],
+    ["る", "り", "ら", "れ", "ろ", "って", "った"],
+];
+
+pub(crate) fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        use Inflection::*;
+
+        let mut rules = Vec::new();
+        let mut add = |inflected: &str, base: &str, inflected_type: u8, base_type: u8, inflections: &'static [Inflection]| {
+            rules.push(Rule {
+                inflected: inflected.to_string(),
+                base: base.to_string(),
+                inflected_type,
+                base_type,
+                inflections,
+                iku: false,
+                yoi: false,
+            });
+        };
+
+        for (inflected, inflected_type, inflections) in [
+            ("た", FINAL, &[Past][..]),
+            ("て", TE, &[Te]),
+            ("ない", ADJECTIVE, &[Negative]),
+            ("ます", MASU, &[Polite]),
+            ("たい", ADJECTIVE, &[Desire]),
+            ("よう", FINAL, &[Volitional]),
+            ("られる", ICHIDAN, &[Potential]),
+            ("られる", ICHIDAN, &[Passive]),
+            ("させる", ICHIDAN, &[Causative]),
+            ("ろ", FINAL, &[Imperative]),
+            ("れば", FINAL, &[Conditional]),
+            ("たら", FINAL, &[Tara]),
+        ] {
+            add(inflected, "る", inflected_type, ICHIDAN, inflections);
+        }
+
+        for [u, i, a, e, o, te, ta] in GODAN_ROWS {
+            add(ta, u, FINAL, GODAN, &[Past]);
+            add(te, u, TE, GODAN, &[Te]);
+            add(&format!("{a}ない"), u, ADJECTIVE, GODAN, &[Negative]);
+            add(&format!("{i}ます"), u, MASU, GODAN, &[Polite]);
+            add(&format!("{i}たい"), u, ADJECTIVE, GODAN, &[Desire]);
+            add(&format!("{o}う"), u, FINAL, GODAN, &[Volitional]);
+            add(&format!("{e}る"), u, ICHIDAN, GODAN, &[Potential]);
+            add(&format!("{a}れる"), u, ICHIDAN, GODAN, &[Passive]);
+            add(&format!("{a}せる"), u, ICHIDAN, GODAN, &[Causative]);
+            add(e, u, FINAL, GODAN, &[Imperative]);
+            add(&format!("{e}ば"), u, FINAL, GODAN, &[Conditional]);
+            add(&format!("{ta}ら"), u, FINAL, GODAN, &[Tara]);
+        }
+        // ある: ない, not あらない
+        for base in ["ある", "有る", "在る"] {
+            add("ない", base, ADJECTIVE, GODAN, &[Negative]);
+        }
+
+        for (inflected, inflected_type, inflections) in [
+            ("きた", FINAL, &[Past][..]),
+            ("きて", TE, &[Te]),
+            ("こない", ADJECTIVE, &[Negative]),
+            ("きます", MASU, &[Polite]),
+            ("きたい", ADJECTIVE, &[Desire]),
+            ("こよう", FINAL, &[Volitional]),
+            ("こられる", ICHIDAN, &[Potential]),
+            ("こられる", ICHIDAN, &[Passive]),
+            ("こさせる", ICHIDAN, &[Causative]),
+            ("こい", FINAL, &[Imperative]),
+            ("くれば", FINAL, &[Conditional]),
+            ("きたら", FINAL, &[Tara]),
+        ] {
+            add(inflected, "くる", inflected_type, KURU, inflections);
+            // 来た, 来ない: the kanji stands for whichever syllable the form has
+            let okurigana = &inflected[inflected.chars().next().unwrap().len_utf8()..];
+            add(&format!("来{okurigana}"), "来る", inflected_type, KURU, inflections);
+        }
+
+        for (inflected, inflected_type, inflections) in [
+            ("した", FINAL, &[Past][..]),
+            ("して", TE, &[Te]),
+            ("しない", ADJECTIVE, &[Negative]),
+            ("します", MASU, &[Polite]),
+            ("したい", ADJECTIVE, &[Desire]),
+            ("しよう", FINAL, &[Volitional]),
+            ("できる", ICHIDAN, &[Potential]),
+            ("される", ICHIDAN, &[Passive]),
+            ("させる", ICHIDAN, &[Causative]),
+            ("しろ", FINAL, &[Imperative]),
+            ("すれば", FINAL, &[Conditional]),
+            ("したら", FINAL, &[Tara]),
+        ] {
+            add(inflected, "する", inflected_type, SURU, inflections);
+        }
+
+        let adjective_endings = [
+            ("かった", FINAL, &[Past][..]),
+            ("くて", FINAL, &[Te]),
+            ("くない", ADJECTIVE, &[Negative]),
+            ("く", FINAL, &[Adverbial]),
+            ("ければ", FINAL, &[Conditional]),
+            ("かったら", FINAL, &[Tara]),
+        ];
+        for (inflected, inflected_type, inflections) in adjective_endings {
+            add(inflected, "い", inflected_type, ADJECTIVE, inflections);
+        }
+
+        for (inflected, inflections) in [
+            ("ました", &[Past][..]),
+            ("ません", &[Negative]),
+            ("ませんでした", &[Negative, Past]),
+            ("ましょう", &[Volitional]),
+            ("まして", &[Te]),
+            ("ましたら", &[Tara]),
+        ] {
+            add(inflected, "ます", FINAL, MASU, inflections);
+        }
+
+        for te in ["て", "で"] {
+            let contracted = if te == "て" { "ちゃう" } else { "じゃう" };
+            add(&format!("{te}いる"), te, ICHIDAN, TE, &[Progressive]);
+            add(&format!("{te}る"), te, ICHIDAN, TE, &[Progressive]);
+            add(&format!("{te}しまう"), te, GODAN, TE, &[Completion]);
+            add(contracted, te, GODAN, TE, &[Completion]);
+        }
+
+        // 行く, 逝く: 行った, not 行いた
+        for (inflected, inflected_type, inflections) in [
+            ("った", FINAL, &[Past][..]),
+            ("って", TE, &[Te]),
+            ("ったら", FINAL, &[Tara]),
+        ] {
+            rules.push(Rule {
+                inflected: inflected.to_string(),
+                base: "く".to_string(),
+                inflected_type,
+                base_type: GODAN,
+                inflections,
+                iku: true,
+                yoi: false,
+            });
+        }
+
+        // いい and かっこいい conjugate from よい, かわいい does not
+        for (inflected, inflected_type, inflections) in adjective_endings {
+            rules.push(Rule {
+                inflected: format!("よ{inflected}"),
+                base: "いい".to_string(),
+                inflected_type,
+                base_type: ADJECTIVE,
+                inflections,
+                iku: false,
+                yoi: true,
+            });
+        }
+
+        rules
+    })
+}
+
+/// A possible dictionary form of an inflected word, not yet checked against JMDict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deinflection {
+    /// Dictionary form, e.g. 買う for 買った
+    pub lemma: String,
+    pub class: ConjugationClass,
+    /// Steps from the dictionary form to the input
+    pub inflections: Vec<Inflection>,
+    // Indices into rules(), in the same order as `inflections`
+    rules: Vec<usize>,
+}
+
+impl Deinflection {
+    /// Inflect another dictionary form of the same class the same way
+    ///
+    /// Replays the exact suffixes of the input (てる stays てる), falling back
+    /// to [`conjugate`] when the endings differ, as between 来る and くる.
+    fn inflect(&self, lemma: &str) -> Option<String> {
+        apply_rules(lemma, &self.rules_for(lemma)?)
+    }
+
+    /// Rules inflecting `lemma` as [`Deinflection::inflect`] does
+    pub(crate) fn rules_for(&self, lemma: &str) -> Option<Vec<usize>> {
+        match apply_rules(lemma, &self.rules) {
+            Some(_) => Some(self.rules.clone()),
+            None => conjugation_rules(lemma, self.class, &self.inflections),
+        }
+    }
+}
+
+/// Apply rules in order, `None` if an ending does not match
+pub(crate) fn apply_rules(lemma: &str, chain: &[usize]) -> Option<String> {
+    let mut text = lemma.to_string();
+    for &i in chain {
+        let rule = &rules()[i];
+        let stem = text.strip_suffix(rule.base.as_str())?;
+        text = format!("{stem}{}", rule.inflected);
+    }
+    Some(text)
+}
+
+/// Whether `lemma` is read いく or ゆく, or ends in such a verb (逝く,
+/// 持って行く), and so makes 〜った and 〜って
+///
+/// Kanji spellings are looked up among the JMDict 〜く verbs with such a reading.
+pub(crate) fn is_iku_verb(lemma: &str) -> bool {
+    static SPELLINGS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    let reading = katakana_to_hiragana(lemma);
+    if reading.ends_with("いく") || reading.ends_with("ゆく") || lemma.ends_with("行く") {
+        return true;
+    }
+    SPELLINGS.get_or_init(|| {
+        jmdict::entries()
+            .filter(|e| e.reading_elements().any(|r| r.text.ends_with("いく") || r.text.ends_with("ゆく")))
+            .filter(|e| e.senses().flat_map(|s| s.parts_of_speech()).any(|p| p.code().starts_with("v5k")))
+            .flat_map(|e| e.kanji_elements().map(|k| k.text))
+            .collect()
+    })
+    .contains(lemma)
+}
+
+/// Whether `lemma` is いい or a compound of it (かっこいい, 仲いい), and so
+/// makes よかった and よくない
+///
+/// Compounds are the JMDict spellings ending in いい of yoi/ii-class
+/// adjectives (`adj-ix`), so かわいい is not one.
+pub(crate) fn is_ii_adjective(lemma: &str) -> bool {
+    static SPELLINGS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    if lemma == "いい" {
+        return true;
+    }
+    lemma.ends_with("いい") && SPELLINGS.get_or_init(|| {
+        jmdict::entries()
+            .filter(|e| e.senses().flat_map(|s| s.parts_of_speech()).any(|p| p.code() == "adj-ix"))
+            .flat_map(|e| e.kanji_elements().map(|k| k.text).chain(e.reading_elements().map(|r| r.text)))
+            .filter(|text| text.ends_with("いい"))
+            .collect()
+    })
+    .contains(lemma)
+}
+
+/// Every way `word` can be an inflected verb or i-adjective
+///
+/// Candidates are not checked against JMDict: 買った gives 買う, 買つ and
+/// 買る. Dictionary forms themselves are not returned.
+pub fn deinflect(word: &str) -> Vec<Deinflection> {
+    let rules = rules();
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = vec![(word.to_string(), u8::MAX, Vec::<usize>::new())];
+
+    while let Some((text, word_type, applied)) = queue.pop() {
+        if applied.len() >= MAX_INFLECTIONS {
+            continue;
+        }
+        for (i, rule) in rules.iter().enumerate() {
+            if rule.inflected_type & word_type == 0 {
+                continue;
+            }
+            let Some(stem) = text.strip_suffix(rule.inflected.as_str()) else {
+                continue;
+            };
+            // A lone ending such as う is not a word
+            if stem.is_empty() && rule.base.chars().count() < 2 {
+                continue;
+            }
+
+            let lemma = format!("{stem}{}", rule.base);
+            // 買く has no 買った, and 行いた is not 行く
+            let iku_rule = |r: &Rule| r.iku && r.base == rule.base && r.inflections == rule.inflections;
+            if rule.iku != is_iku_verb(&lemma) && (rule.iku || rules.iter().any(iku_rule)) {
+                continue;
+            }
+            // かわよかった is not かわいい, and いかった is not いい
+            let yoi_rule = |r: &Rule| r.yoi && r.base_type == rule.base_type && r.inflections == rule.inflections;
+            if rule.yoi != is_ii_adjective(&lemma) && (rule.yoi || rules.iter().any(yoi_rule)) {
+                continue;
+            }
+            let mut chain = vec![i];
+            chain.extend(&applied);
+            if !seen.insert((lemma.clone(), chain.clone())) {
+                continue;
+            }
+
+            if let Some(class) = ConjugationClass::from_word_type(rule.base_type) {
+                results.push(Deinflection {
+                    lemma: lemma.clone(),
+                    class,
+                    inflections: chain.iter().flat_map(|&r| rules[r].inflections.iter().copied()).collect(),
+                    rules: chain.clone(),
+                });
+            }
+            queue.push((lemma, rule.base_type, chain));
+        }
+    }
+
+    results
+}
+
+/// Conjugate a dictionary form, e.g. 買う with `[Polite, Past]` to 買いました
+///
+/// Returns `None` if the lemma does not end like its class or the steps do
+/// not chain (nothing follows `Past`). Contracted forms such as 〜てる are
+/// never produced.
+pub fn conjugate(lemma: &str, class: ConjugationClass, inflections: &[Inflection]) -> Option<String> {
+    apply_rules(lemma, &conjugation_rules(lemma, class, inflections)?)
+}
+
+/// Rules [`conjugate`] applies, in order
+pub(crate) fn conjugation_rules(lemma: &str, class: ConjugationClass, inflections: &[Inflection]) -> Option<Vec<usize>> {
+    let mut text = lemma.to_string();
+    let mut word_type = class.word_type();
+    let mut rest = inflections;
+    let mut chain = Vec::new();
+    let iku = is_iku_verb(lemma);
+    let ii = is_ii_adjective(lemma);
+
+    while !rest.is_empty() {
+        // Prefer the rule covering the most steps, then the most specific
+        // ending (ある over る), then the 行く rules for 行く, then the first listed
+        let (i, rule) = rules().iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, r)| r.base_type & word_type != 0 && text.ends_with(&r.base) && rest.starts_with(r.inflections))
+            .filter(|(_, r)| (iku || !r.iku) && (ii || !r.yoi))
+            .max_by_key(|(_, r)| (r.inflections.len(), r.base.chars().count(), r.iku))?;
+        text = format!("{}{}", &text[..text.len() - rule.base.len()], rule.inflected);
+        word_type = rule.inflected_type;
+        rest = &rest[rule.inflections.len()..];
+        chain.push(i);
+    }
+
+    Some(chain)
+}
+
+/// A conjugated word, e.g. 飼った from 飼う
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct InflectedWord {
+    /// Inflected spelling
+    pub text: String,
+    /// Inflected reading (hiragana)
+    pub reading: String,
+    /// Steps from the dictionary form to `text`
+    pub inflections: Vec<Inflection>,
+    /// The JMDict word inflected; for nouns taking する, the noun itself
+    pub lemma: WordFrequency,
+}
+
+/// An [`InflectedWord`] whose lemma carries its NHK pitch accent
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct InflectedWordWithPitch {
+    pub text: String,
+    pub reading: String,
+    pub inflections: Vec<Inflection>,
+    /// Accents of the inflected form, one per accent of the lemma, see
+    /// [`crate::conjugation_pitch`]
+    pub pitch_accent: Vec<u8>,
+    /// The JMDict word inflected, with the accent of its dictionary form
+    pub lemma: WordFrequencyWithPitch,
+}
+
+/// A JMDict word some deinflection candidate is the dictionary form of
+struct Lemma<'d> {
+    entry: jmdict::Entry,
+    /// Spelling or reading the candidate matched, without する for nouns
+    text: &'static str,
+    deinflection: &'d Deinflection,
+    /// `text` is a noun inflected with する
+    suru_noun: bool,
+}
+
+impl Lemma<'_> {
+    fn inflect(&self, text: &str) -> Option<String> {
+        self.deinflection.inflect(&self.dictionary_form(text))
+    }
+
+    /// `text` with する for nouns
+    fn dictionary_form(&self, text: &str) -> String {
+        let suffix = if self.suru_noun { "する" } else { "" };
+        format!("{text}{suffix}")
+    }
+}
+
+/// A match of [`collect_inflected`]
+struct Collected<W> {
+    text: String,
+    reading: String,
+    inflections: Vec<Inflection>,
+    lemma: W,
+    class: ConjugationClass,
+    /// Dictionary form of the reading, with する for nouns
+    lemma_reading: String,
+    /// Rules taking `lemma_reading` to `reading`
+    reading_rules: Vec<usize>,
+}
+
+/// JMDict words whose kanji spellings (or readings) the candidates are, with a matching part of speech
+fn find_lemmas(candidates: &[Deinflection], by_kanji: bool) -> Vec<Lemma<'_>> {
+    let mut keys: HashMap<&str, Vec<(usize, bool)>> = HashMap::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        keys.entry(&candidate.lemma).or_default().push((i, false));
+        if candidate.class == ConjugationClass::Suru
+            && let Some(noun) = candidate.lemma.strip_suffix("する").filter(|n| !n.is_empty())
+        {
+            keys.entry(noun).or_default().push((i, true));
+        }
+    }
+
+    let mut lemmas = Vec::new();
+    for entry in jmdict::entries() {
+        let texts: Vec<&'static str> = if by_kanji {
+            entry.kanji_elements().map(|k| k.text).collect()
+        } else {
+            entry.reading_elements().map(|r| r.text).collect()
+        };
+        for text in texts {
+            for &(i, suru_noun) in keys.get(text).into_iter().flatten() {
+                let deinflection = &candidates[i];
+                let conjugates = entry.senses()
+                    .flat_map(|s| s.parts_of_speech())
+                    .any(|p| deinflection.class.matches(p.code(), &deinflection.lemma, suru_noun));
+                if conjugates {
+                    lemmas.push(Lemma { entry, text, deinflection, suru_noun });
+                }
+            }
+        }
+    }
+    lemmas
+}
+
+/// Find the dictionary forms of an inflected word and the words it sounds like
+///
+/// 買った gives 買う (`[Past]`, [`MatchKind::KanjiElement`]) and, sharing the
+/// reading かった, 飼った, 勝った and 刈った ([`MatchKind::SharedReading`]).
+/// Kana input such as かいました matches every spelling read that way.
+/// Dictionary forms are not deinflected; look them up with [`crate::find`].
+pub fn find_inflected(word: &str) -> Vec<InflectedWord> {
+    let mut words: Vec<InflectedWord> = collect_inflected(word, WordFrequency::jmdict)
+        .into_iter()
+        .map(|c| InflectedWord { text: c.text, reading: c.reading, inflections: c.inflections, lemma: c.lemma })
+        .collect();
+    words.sort_by_key(|w| std::cmp::Reverse(w.lemma.frequency_score));
+    words
+}
+
+/// [`find_inflected`] with the NHK accent of each dictionary form
+///
+/// Given kana such as かった, lists every verb and adjective form read that
+/// way (買った, 勝った, 飼った, 刈った), most frequent lemma first.
+pub fn find_inflected_with_nhk(word: &str) -> Vec<InflectedWordWithPitch> {
+    let mut words: Vec<InflectedWordWithPitch> = collect_inflected(word, WordFrequencyWithPitch::jmdict)
+        .into_iter()
+        .map(|c| {
+            let mut pitch_accent: Vec<u8> = Vec::new();
+            for &accent in &c.lemma.pitch_accent {
+                if let Some(accent) = accent_after_rules(&c.lemma_reading, c.class, accent, &c.reading_rules)
+                    && !pitch_accent.contains(&accent)
+                {
+                    pitch_accent.push(accent);
+                }
+            }
+            InflectedWordWithPitch { text: c.text, reading: c.reading, inflections: c.inflections, pitch_accent, lemma: c.lemma }
+        })
+        .collect();
+    words.sort_by_key(|w| std::cmp::Reverse(w.lemma.frequency_score));
+    words
+}
+
+/// [`find_inflected`] as result words, for [`crate::find`] on input JMDict
+/// does not list; frequency and entry are the lemma's
+pub(crate) fn inflected_words(word: &str) -> Vec<WordFrequency> {
+    let mut seen = HashSet::new();
+    find_inflected(word).into_iter()
+        .filter(|w| seen.insert((w.text.clone(), w.reading.clone())))
+        .map(|w| WordFrequency { text: w.text, reading: w.reading, ..w.lemma })
+        .collect()
+}
+
+/// [`find_inflected_with_nhk`] as a [`FindWithNhkResult`], for
+/// [`crate::find_with_nhk`] on input JMDict does not list
+///
+/// Homophones are told apart by the accents of their inflected forms.
+pub(crate) fn inflected_result(word: &str) -> FindWithNhkResult {
+    let mut seen = HashSet::new();
+    let words: Vec<WordFrequencyWithPitch> = find_inflected_with_nhk(word).into_iter()
+        .filter(|w| seen.insert((w.text.clone(), w.reading.clone())))
+        .map(|w| WordFrequencyWithPitch {
+            pitch_source: PitchSource::nhk(&w.pitch_accent),
+            text: w.text,
+            reading: w.reading,
+            pitch_accent: w.pitch_accent,
+            ..w.lemma
+        })
+        .collect();
+
+    match distinct_word_count(words.iter()) {
+        0 => return FindWithNhkResult::NotFound,
+        1 => return FindWithNhkResult::NoHomophones,
+        _ => {}
+    }
+    let mut target_readings: Vec<String> = Vec::new();
+    for w in &words {
+        if !target_readings.contains(&w.reading) {
+            target_readings.push(w.reading.clone());
+        }
+    }
+
+    // 買った spells one word; かった could be any of them
+    let Some(target) = words.iter().find(|w| w.text == word && w.match_kind != MatchKind::SharedReading).cloned() else {
+        return FindWithNhkResult::MultipleMatches { homophones: words, target_readings, affixes: Vec::new() };
+    };
+    let (true_homophones, different_pitch_homophones) = words.into_iter()
+        .filter(|w| w.reading == target.reading)
+        .partition(|w| {
+            w.text == target.text
+                || w.pitch_accent.is_empty()
+                || target.pitch_accent.is_empty()
+                || target.pitch_accent.iter().any(|a| w.pitch_accent.contains(a))
+        });
+    FindWithNhkResult::UniqueMatch {
+        true_homophones,
+        different_pitch_homophones,
+        target_readings: vec![target.reading],
+        affixes: Vec::new(),
+    }
+}
+
+/// Every match of the input and of its readings
+///
+/// The same form reached twice (from the input's spelling, then from its
+/// reading) is listed once, with the first match kind.
+fn collect_inflected<W>(
+    word: &str,
+    make_lemma: impl Fn(u32, &str, &str, &jmdict::Priority, MatchKind) -> W,
+) -> Vec<Collected<W>> {
+    let hiragana = katakana_to_hiragana(word);
+    let mut words = Vec::new();
+    let mut seen = HashSet::new();
+    let mut target_readings: Vec<String> = Vec::new();
+    if hiragana.chars().all(|c| matches!(c, 'ぁ'..='ゖ' | 'ー')) {
+        target_readings.push(hiragana.clone());
+    }
+
+    // Lemmas the input spells, e.g. 買う for 買った
+    let candidates = deinflect(word);
+    for lemma in find_lemmas(&candidates, true) {
+        let Some(kanji) = lemma.entry.kanji_elements().find(|k| k.text == lemma.text) else {
+            continue;
+        };
+        for reading in lemma.entry.reading_elements() {
+            let lemma_reading = lemma.dictionary_form(reading.text);
+            let Some(reading_rules) = lemma.deinflection.rules_for(&lemma_reading) else {
+                continue;
+            };
+            let Some(inflected_reading) = apply_rules(&lemma_reading, &reading_rules) else {
+                continue;
+            };
+            if !target_readings.contains(&inflected_reading) {
+                target_readings.push(inflected_reading.clone());
+            }
+            let inflections = &lemma.deinflection.inflections;
+            if seen.insert((word.to_string(), inflected_reading.clone(), lemma.entry.number, inflections.clone())) {
+                words.push(Collected {
+                    text: word.to_string(),
+                    reading: inflected_reading,
+                    inflections: inflections.clone(),
+                    lemma: make_lemma(lemma.entry.number, kanji.text, reading.text, &kanji.priority, MatchKind::KanjiElement),
+                    class: lemma.deinflection.class,
+                    lemma_reading,
+                    reading_rules,
+                });
+            }
+        }
+    }
+
+    // Every word inflecting to one of those readings, e.g. 飼った and 刈った
+    for target in &target_readings {
+        let candidates = deinflect(target);
+        let match_kind = reading_match_kind(target, word, &hiragana);
+        for lemma in find_lemmas(&candidates, false) {
+            let Some(reading) = lemma.entry.reading_elements().find(|r| r.text == lemma.text) else {
+                continue;
+            };
+            let lemma_reading = lemma.dictionary_form(reading.text);
+            let Some(reading_rules) = lemma.deinflection.rules_for(&lemma_reading) else {
+                continue;
+            };
+            let spellings: Vec<(&str, jmdict::Priority)> = if lemma.entry.kanji_elements().count() == 0 {
+                vec![(reading.text, reading.priority)]
+            } else {
+                lemma.entry.kanji_elements().map(|k| (k.text, k.priority)).collect()
+            };
+            for (text, priority) in spellings {
+                let Some(inflected) = lemma.inflect(text) else {
+                    continue;
+                };
+                let inflections = &lemma.deinflection.inflections;
+                if seen.insert((inflected.clone(), target.clone(), lemma.entry.number, inflections.clone())) {
+                    words.push(Collected {
+                        text: inflected,
+                        reading: target.clone(),
+                        inflections: inflections.clone(),
+                        lemma: make_lemma(lemma.entry.number, text, reading.text, &priority, match_kind),
+                        class: lemma.deinflection.class,
+                        lemma_reading: lemma_reading.clone(),
+                        reading_rules: reading_rules.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Inflection::*;
+
+    fn lemmas(word: &str) -> Vec<(String, Vec<Inflection>)> {
+        deinflect(word).into_iter().map(|d| (d.lemma, d.inflections)).collect()
+    }
+
+    #[test]
+    fn test_deinflect() {
+        let katta = lemmas("買った");
+        for lemma in ["買う", "買つ", "買る"] {
+            assert!(katta.contains(&(lemma.to_string(), vec![Past])), "{lemma}");
+        }
+        assert!(lemmas("食べさせられなかった").contains(&("食べる".to_string(), vec![Causative, Passive, Negative, Past])));
+        assert!(lemmas("かいませんでした").contains(&("かう".to_string(), vec![Polite, Negative, Past])));
+        assert!(lemmas("来なかった").contains(&("来る".to_string(), vec![Negative, Past])));
+        assert!(lemmas("高くない").contains(&("高い".to_string(), vec![Negative])));
+        assert!(lemmas("買う").iter().all(|(lemma, _)| lemma != "買う"));
+
+        // Verbs read いく take った whatever their spelling
+        assert!(lemmas("逝った").contains(&("逝く".to_string(), vec![Past])));
+        assert!(!lemmas("逝いた").iter().any(|(lemma, _)| lemma == "逝く"));
+        assert!(!lemmas("かった").iter().any(|(lemma, _)| lemma == "かく"));
+        assert!(lemmas("ない").contains(&("ある".to_string(), vec![Negative])));
+
+        // Only いい and its compounds take よ
+        assert!(lemmas("よかった").contains(&("いい".to_string(), vec![Past])));
+        assert!(!lemmas("いかった").iter().any(|(lemma, _)| lemma == "いい"));
+        assert!(!lemmas("かわよかった").iter().any(|(lemma, _)| lemma == "かわいい"));
+        assert!(lemmas("かわいかった").contains(&("かわいい".to_string(), vec![Past])));
+    }
+
+    #[test]
+    fn test_conjugate() {
+        assert_eq!(conjugate("買う", ConjugationClass::Godan, &[Polite, Past]).as_deref(), Some("買いました"));
+        assert_eq!(conjugate("行く", ConjugationClass::Godan, &[Te, Progressive]).as_deref(), Some("行っている"));
+        assert_eq!(conjugate("くる", ConjugationClass::Kuru, &[Negative]).as_deref(), Some("こない"));
+        assert_eq!(conjugate("高い", ConjugationClass::IAdjective, &[Negative, Past]).as_deref(), Some("高くなかった"));
+        assert_eq!(conjugate("買う", ConjugationClass::Godan, &[Past, Past]), None);
+
+        assert_eq!(conjugate("逝く", ConjugationClass::Godan, &[Past]).as_deref(), Some("逝った"));
+        if cfg!(feature = "uncommon") {
+            // 往く is an uncommon spelling, so only known as a いく verb with the feature on
+            assert_eq!(conjugate("往く", ConjugationClass::Godan, &[Tara]).as_deref(), Some("往ったら"));
+        }
+        assert_eq!(conjugate("持って行く", ConjugationClass::Godan, &[Te]).as_deref(), Some("持って行って"));
+        assert_eq!(conjugate("聞く", ConjugationClass::Godan, &[Past]).as_deref(), Some("聞いた"));
+
+        // ある has no あらない
+        assert_eq!(conjugate("ある", ConjugationClass::Godan, &[Negative]).as_deref(), Some("ない"));
+        assert_eq!(conjugate("有る", ConjugationClass::Godan, &[Negative, Past]).as_deref(), Some("なかった"));
+        assert_eq!(conjugate("ある", ConjugationClass::Godan, &[Polite, Negative]).as_deref(), Some("ありません"));
+
+        assert_eq!(conjugate("いい", ConjugationClass::IAdjective, &[Negative]).as_deref(), Some("よくない"));
+        assert_eq!(conjugate("かっこいい", ConjugationClass::IAdjective, &[Past]).as_deref(), Some("かっこよかった"));
+        assert_eq!(conjugate("格好いい", ConjugationClass::IAdjective, &[Tara]).as_deref(), Some("格好よかったら"));
+        assert_eq!(conjugate("かわいい", ConjugationClass::IAdjective, &[Past]).as_deref(), Some("かわいかった"));
+        assert_eq!(conjugate("かわいい", ConjugationClass::IAdjective, &[Negative]).as_deref(), Some("かわいくない"));
+    }
+
+    #[test]
+    fn test_find_inflected() {
+        let results = find_inflected("買った");
+        let kau = results.iter().find(|w| w.text == "買った").unwrap();
+        assert_eq!(kau.lemma.text, "買う");
+        assert_eq!(kau.reading, "かった");
+        assert_eq!(kau.inflections, vec![Past]);
+        assert_eq!(kau.lemma.match_kind, MatchKind::KanjiElement);
+
+        let texts: Vec<&str> = results.iter().map(|w| w.text.as_str()).collect();
+        for homophone in ["飼った", "勝った", "刈った"] {
+            assert!(texts.contains(&homophone), "{homophone}");
+        }
+        for i in 1..results.len() {
+            assert!(results[i - 1].lemma.frequency_score >= results[i].lemma.frequency_score);
+        }
+
+        let polite = find_inflected("かいました");
+        let texts: Vec<&str> = polite.iter().map(|w| w.text.as_str()).collect();
+        assert!(texts.contains(&"買いました") && texts.contains(&"飼いました"));
+        assert!(polite.iter().all(|w| w.inflections == vec![Polite, Past] && w.lemma.match_kind == MatchKind::Reading));
+
+        // Contracted forms keep their ending, nouns take する
+        assert!(find_inflected("見てる").iter().any(|w| w.text == "診てる"));
+        let suru = find_inflected("こうせいした");
+        assert!(suru.iter().any(|w| w.text == "校正した" && w.lemma.text == "校正"));
+
+        assert!(find_inflected("買う").is_empty());
+
+        let itta = find_inflected("逝った");
+        assert!(itta.iter().any(|w| w.lemma.text == "逝く" && w.reading == "いった"));
+        let nai = find_inflected("なかった");
+        assert!(nai.iter().any(|w| w.lemma.text == "有る" && w.inflections == vec![Negative, Past]));
+    }
+
+    #[test]
+    fn test_homophones_across_lemmas() {
+        let results = find_inflected_with_nhk("かった");
+        let lemmas: Vec<&str> = results.iter().map(|w| w.lemma.text.as_str()).collect();
+        for lemma in ["買う", "勝つ", "飼う", "刈る"] {
+            assert!(lemmas.contains(&lemma), "{lemma}");
+        }
+        assert!(results.iter().all(|w| w.reading == "かった" && w.lemma.match_kind == MatchKind::Reading));
+
+        // 高い + 〜かった is たかかった, not かった
+        assert!(!lemmas.contains(&"高い"));
+
+        let kau = results.iter().find(|w| w.text == "買った").unwrap();
+        assert_eq!(kau.lemma.pitch_accent, vec![0]);
+        assert_eq!(kau.lemma.pitch_source, crate::PitchSource::Nhk);
+
+        // 飼った か'った is told apart from 買った by its accent
+        let kau = results.iter().find(|w| w.text == "飼った").unwrap();
+        assert_eq!(kau.pitch_accent, vec![1]);
+        let negative = find_inflected_with_nhk("かわない");
+        let accents: Vec<(&str, &[u8])> = negative.iter().map(|w| (w.text.as_str(), w.pitch_accent.as_slice())).collect();
+        assert!(accents.contains(&("買わない", &[0][..])) && accents.contains(&("飼わない", &[2][..])));
+    }
+}
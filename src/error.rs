@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// Why a lookup produced no words
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    /// The input is empty or only whitespace
+    EmptyInput,
+    /// The input contains no Japanese characters (e.g. "hello")
+    UnsupportedScript,
+    /// The input mixes Japanese with other scripts and matched nothing (e.g. "hello カイ")
+    MixedScript,
+    /// The input is Japanese but no dictionary word has that spelling or reading
+    NotInDictionary { word: String },
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupError::EmptyInput => write!(f, "input is empty"),
+            LookupError::UnsupportedScript => write!(f, "input contains no Japanese characters"),
+            LookupError::MixedScript => write!(f, "input mixes Japanese with other scripts"),
+            LookupError::NotInDictionary { word } => write!(f, "{word} is not in the dictionary"),
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// Reject input that cannot possibly match before scanning the dictionary
+///
+/// A leading 〜 or ～ marks a suffix (〜あめ) and is not checked.
+pub(crate) fn check_input(word: &str) -> Result<(), LookupError> {
+    let word = word.trim_start_matches(['〜', '～']);
+    if word.trim().is_empty() {
+        Err(LookupError::EmptyInput)
+    } else if !word.chars().any(crate::kana_utils::is_japanese) {
+        Err(LookupError::UnsupportedScript)
+    } else {
+        Ok(())
+    }
+}
+
+/// Error for Japanese input that matched nothing in the dictionary
+pub(crate) fn not_found(word: &str) -> LookupError {
+    if word.trim_start_matches(['〜', '～']).chars().all(crate::kana_utils::is_japanese) {
+        LookupError::NotInDictionary { word: word.to_string() }
+    } else {
+        LookupError::MixedScript
+    }
+}
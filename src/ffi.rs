@@ -0,0 +1,330 @@
+//! C ABI, enabled with the `ffi` feature.
+//!
+//! The header lives in `include/jaydar.h` and is generated with
+//! `cbindgen --config cbindgen.toml --output include/jaydar.h`.
+//!
+//! Every pointer returned by this module is owned by the caller and must be
+//! released with the matching `jaydar_*_free` function.
+
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+use crate::{FindWithNhkResult, MatchKind, WordFrequencyWithPitch};
+
+/// Discriminant of `FindWithNhkResult`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JaydarResultKind {
+    NoHomophones = 0,
+    UniqueMatch = 1,
+    MultipleMatches = 2,
+    NotFound = 3,
+    /// A suffix such as 〜あめ; its rules are in the JSON result only
+    Affix = 4,
+}
+
+/// How a word was reached from the input, see `MatchKind`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JaydarMatchKind {
+    KanjiElement = 0,
+    Reading = 1,
+    KatakanaConversion = 2,
+    KatakanaSelfEntry = 3,
+    SharedReading = 4,
+}
+
+impl From<MatchKind> for JaydarMatchKind {
+    fn from(kind: MatchKind) -> Self {
+        match kind {
+            MatchKind::KanjiElement => JaydarMatchKind::KanjiElement,
+            MatchKind::Reading => JaydarMatchKind::Reading,
+            MatchKind::KatakanaConversion => JaydarMatchKind::KatakanaConversion,
+            MatchKind::KatakanaSelfEntry => JaydarMatchKind::KatakanaSelfEntry,
+            MatchKind::SharedReading => JaydarMatchKind::SharedReading,
+        }
+    }
+}
+
+/// A homophone with its pitch accents
+#[repr(C)]
+pub struct JaydarWord {
+    /// NUL-terminated UTF-8
+    pub text: *mut c_char,
+    /// NUL-terminated UTF-8, as the dictionary spells it (katakana for loanwords and many names)
+    pub reading: *mut c_char,
+    pub frequency_score: u32,
+    pub is_common: bool,
+    /// Pitch accents in order of preference, empty if unknown
+    pub pitch_accent: *mut u8,
+    pub pitch_accent_len: usize,
+    pub match_kind: JaydarMatchKind,
+}
+
+/// An owned array of words
+#[repr(C)]
+pub struct JaydarWordArray {
+    pub words: *mut JaydarWord,
+    pub len: usize,
+}
+
+/// Result of `jaydar_find_with_nhk`
+#[repr(C)]
+pub struct JaydarResult {
+    pub kind: JaydarResultKind,
+    /// `UniqueMatch`: the true homophones (same pitch).
+    /// `MultipleMatches`: all homophones. Empty for `NoHomophones` and `NotFound`.
+    pub homophones: JaydarWordArray,
+    /// `UniqueMatch` only: homophones with a different pitch accent
+    pub different_pitch_homophones: JaydarWordArray,
+    /// Readings the input resolved to, as NUL-terminated UTF-8 strings.
+    /// Empty for `NoHomophones` and `NotFound`.
+    pub target_readings: *mut *mut c_char,
+    pub target_readings_len: usize,
+}
+
+/// Find homophones with pitch accent data.
+///
+/// Returns null if `word` is null or not valid UTF-8. Free the result with
+/// `jaydar_result_free`.
+///
+/// # Safety
+///
+/// `word` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jaydar_find_with_nhk(word: *const c_char) -> *mut JaydarResult {
+    let Some(word) = (unsafe { str_from_ptr(word) }) else {
+        return ptr::null_mut();
+    };
+
+    let result = match crate::find_with_nhk(word) {
+        FindWithNhkResult::NotFound => JaydarResult {
+            kind: JaydarResultKind::NotFound,
+            homophones: word_array(Vec::new()),
+            different_pitch_homophones: word_array(Vec::new()),
+            target_readings: ptr::null_mut(),
+            target_readings_len: 0,
+        },
+        FindWithNhkResult::NoHomophones => JaydarResult {
+            kind: JaydarResultKind::NoHomophones,
+            homophones: word_array(Vec::new()),
+            different_pitch_homophones: word_array(Vec::new()),
+            target_readings: ptr::null_mut(),
+            target_readings_len: 0,
+        },
+        FindWithNhkResult::Affix { .. } => JaydarResult {
+            kind: JaydarResultKind::Affix,
+            homophones: word_array(Vec::new()),
+            different_pitch_homophones: word_array(Vec::new()),
+            target_readings: ptr::null_mut(),
+            target_readings_len: 0,
+        },
+        FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, target_readings, .. } => {
+            let (target_readings, target_readings_len) = string_array(target_readings);
+            JaydarResult {
+                kind: JaydarResultKind::UniqueMatch,
+                homophones: word_array(true_homophones),
+                different_pitch_homophones: word_array(different_pitch_homophones),
+                target_readings,
+                target_readings_len,
+            }
+        }
+        FindWithNhkResult::MultipleMatches { homophones, target_readings, .. } => {
+            let (target_readings, target_readings_len) = string_array(target_readings);
+            JaydarResult {
+                kind: JaydarResultKind::MultipleMatches,
+                homophones: word_array(homophones),
+                different_pitch_homophones: word_array(Vec::new()),
+                target_readings,
+                target_readings_len,
+            }
+        }
+    };
+
+    Box::into_raw(Box::new(result))
+}
+
+/// Find homophones with pitch accent data, serialized as JSON.
+///
+/// The JSON object has a `kind` field holding the `FindWithNhkResult`
+/// variant name. Returns null if `word` is null or not valid UTF-8. Free the
+/// result with `jaydar_string_free`.
+///
+/// # Safety
+///
+/// `word` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jaydar_find_with_nhk_json(word: *const c_char) -> *mut c_char {
+    let Some(word) = (unsafe { str_from_ptr(word) }) else {
+        return ptr::null_mut();
+    };
+
+    match serde_json::to_string(&crate::find_with_nhk(word)) {
+        Ok(json) => into_c_string(json),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Free a result returned by `jaydar_find_with_nhk`. Null is ignored.
+///
+/// # Safety
+///
+/// `result` must be null or a pointer returned by `jaydar_find_with_nhk`
+/// that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jaydar_result_free(result: *mut JaydarResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = unsafe { Box::from_raw(result) };
+    unsafe {
+        free_word_array(result.homophones);
+        free_word_array(result.different_pitch_homophones);
+        free_string_array(result.target_readings, result.target_readings_len);
+    }
+}
+
+/// Free a string returned by `jaydar_find_with_nhk_json`. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by this library that has not been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jaydar_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+unsafe fn str_from_ptr<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // Interior NULs cannot occur in dictionary text, but never panic across the FFI boundary
+    CString::new(s).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+fn word_array(words: Vec<WordFrequencyWithPitch>) -> JaydarWordArray {
+    let words: Box<[JaydarWord]> = words.into_iter()
+        .map(|w| {
+            let pitches: Box<[u8]> = w.pitch_accent.into_boxed_slice();
+            let pitch_accent_len = pitches.len();
+            JaydarWord {
+                text: into_c_string(w.text),
+                reading: into_c_string(w.reading),
+                frequency_score: w.frequency_score,
+                is_common: w.is_common,
+                pitch_accent: Box::into_raw(pitches).cast(),
+                pitch_accent_len,
+                match_kind: w.match_kind.into(),
+            }
+        })
+        .collect();
+    let len = words.len();
+    JaydarWordArray {
+        words: Box::into_raw(words).cast(),
+        len,
+    }
+}
+
+fn string_array(strings: Vec<String>) -> (*mut *mut c_char, usize) {
+    let strings: Box<[*mut c_char]> = strings.into_iter().map(into_c_string).collect();
+    let len = strings.len();
+    (Box::into_raw(strings).cast(), len)
+}
+
+unsafe fn free_string_array(strings: *mut *mut c_char, len: usize) {
+    if strings.is_null() {
+        return;
+    }
+    let strings = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(strings, len)) };
+    for &s in strings.iter() {
+        unsafe { jaydar_string_free(s) };
+    }
+}
+
+unsafe fn free_word_array(array: JaydarWordArray) {
+    let words = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(array.words, array.len)) };
+    for word in words.iter() {
+        unsafe {
+            jaydar_string_free(word.text);
+            jaydar_string_free(word.reading);
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(word.pitch_accent, word.pitch_accent_len)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(array: &JaydarWordArray) -> Vec<String> {
+        let words = unsafe { std::slice::from_raw_parts(array.words, array.len) };
+        words.iter()
+            .map(|w| unsafe { CStr::from_ptr(w.text) }.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_with_nhk_structs() {
+        let word = CString::new("構成").unwrap();
+        let result = unsafe { jaydar_find_with_nhk(word.as_ptr()) };
+        assert!(!result.is_null());
+
+        let r = unsafe { &*result };
+        assert_eq!(r.kind, JaydarResultKind::UniqueMatch);
+        assert!(texts(&r.homophones).contains(&"構成".to_string()));
+
+        let words = unsafe { std::slice::from_raw_parts(r.homophones.words, r.homophones.len) };
+        let kousei = words.iter()
+            .find(|w| unsafe { CStr::from_ptr(w.text) }.to_str() == Ok("構成"))
+            .unwrap();
+        let pitches = unsafe { std::slice::from_raw_parts(kousei.pitch_accent, kousei.pitch_accent_len) };
+        assert_eq!(pitches, &[0]);
+        assert_eq!(kousei.match_kind, JaydarMatchKind::KanjiElement);
+
+        let readings = unsafe { std::slice::from_raw_parts(r.target_readings, r.target_readings_len) };
+        assert_eq!(unsafe { CStr::from_ptr(readings[0]) }.to_str(), Ok("こうせい"));
+
+        unsafe { jaydar_result_free(result) };
+    }
+
+    #[test]
+    fn test_find_with_nhk_json() {
+        let word = CString::new("こうせい").unwrap();
+        let json = unsafe { jaydar_find_with_nhk_json(word.as_ptr()) };
+        assert!(!json.is_null());
+
+        let s = unsafe { CStr::from_ptr(json) }.to_str().unwrap();
+        assert!(s.starts_with(r#"{"kind":"MultipleMatches""#));
+        assert!(s.contains("構成"));
+
+        unsafe { jaydar_string_free(json) };
+    }
+
+    #[test]
+    fn test_null_and_invalid_input() {
+        assert!(unsafe { jaydar_find_with_nhk(ptr::null()) }.is_null());
+        assert!(unsafe { jaydar_find_with_nhk_json(ptr::null()) }.is_null());
+
+        let invalid = [0xffu8, 0xfe, 0];
+        assert!(unsafe { jaydar_find_with_nhk(invalid.as_ptr().cast()) }.is_null());
+
+        let unknown = CString::new("ズヴォヅ").unwrap();
+        let result = unsafe { jaydar_find_with_nhk(unknown.as_ptr()) };
+        assert_eq!(unsafe { &*result }.kind, JaydarResultKind::NotFound);
+        assert_eq!(unsafe { &*result }.homophones.len, 0);
+        unsafe { jaydar_result_free(result) };
+
+        // Freeing null is a no-op
+        unsafe {
+            jaydar_result_free(ptr::null_mut());
+            jaydar_string_free(ptr::null_mut());
+        }
+    }
+}
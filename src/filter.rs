@@ -0,0 +1,224 @@
+use jmdict::Enum;
+use std::collections::{HashMap, HashSet};
+
+/// Broad part of speech, grouping JMDict codes: `v5u`, `v1` and `vs` are all [`PartOfSpeech::Verb`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PartOfSpeech {
+    /// `n`, `n-adv`, `n-t`...
+    Noun,
+    /// `pn`
+    Pronoun,
+    /// `v1`, `v5u`, `vk`, and `vs` for nouns taking する
+    Verb,
+    /// `adj-i`, `adj-na`, `adj-no`...
+    Adjective,
+    /// `adv`, `adv-to`
+    Adverb,
+    /// `prt`
+    Particle,
+    /// `aux`, `aux-v`, `aux-adj`, `cop`
+    Auxiliary,
+    /// `conj`
+    Conjunction,
+    /// `int`
+    Interjection,
+    /// `ctr`
+    Counter,
+    /// `num`
+    Numeric,
+    /// `pref`
+    Prefix,
+    /// `suf`
+    Suffix,
+    /// `exp`
+    Expression,
+    /// `unc` and codes not listed above
+    Other,
+}
+
+impl PartOfSpeech {
+    /// Category of a JMDict part-of-speech code
+    pub fn of(code: &str) -> PartOfSpeech {
+        match code {
+            "pn" => PartOfSpeech::Pronoun,
+            "prt" => PartOfSpeech::Particle,
+            "cop" => PartOfSpeech::Auxiliary,
+            "conj" => PartOfSpeech::Conjunction,
+            "int" => PartOfSpeech::Interjection,
+            "ctr" => PartOfSpeech::Counter,
+            "num" => PartOfSpeech::Numeric,
+            "pref" => PartOfSpeech::Prefix,
+            "suf" => PartOfSpeech::Suffix,
+            "exp" => PartOfSpeech::Expression,
+            _ if code.starts_with("adj") => PartOfSpeech::Adjective,
+            _ if code.starts_with("adv") => PartOfSpeech::Adverb,
+            _ if code.starts_with("aux") => PartOfSpeech::Auxiliary,
+            _ if code == "n" || code.starts_with("n-") => PartOfSpeech::Noun,
+            _ if code.starts_with('v') => PartOfSpeech::Verb,
+            _ => PartOfSpeech::Other,
+        }
+    }
+
+    /// Categories of every sense of an entry
+    pub fn of_entry(entry: &jmdict::Entry) -> HashSet<PartOfSpeech> {
+        entry.senses().flat_map(|s| s.parts_of_speech()).map(|p| PartOfSpeech::of(p.code())).collect()
+    }
+}
+
+/// Categories of the given JMDict entries
+pub(crate) fn entry_parts_of_speech(entries: &HashSet<u32>) -> HashMap<u32, HashSet<PartOfSpeech>> {
+    jmdict::entries()
+        .filter(|e| entries.contains(&e.number))
+        .map(|e| (e.number, PartOfSpeech::of_entry(&e)))
+        .collect()
+}
+
+/// Keep the words sharing a part of speech with a word spelled `input`
+///
+/// Words outside JMDict (names, user words) are kept, and nothing is dropped
+/// if no JMDict word is spelled `input`, as when it is a reading.
+pub(crate) fn retain_same_part_of_speech<W>(words: &mut Vec<W>, input: &str, key: impl Fn(&W) -> (&str, Option<u32>)) {
+    let entries: HashSet<u32> = words.iter().filter_map(|w| key(w).1).collect();
+    let categories = entry_parts_of_speech(&entries);
+    let target: HashSet<PartOfSpeech> = words.iter()
+        .filter_map(|w| match key(w) {
+            (text, Some(entry)) if text == input => categories.get(&entry),
+            _ => None,
+        })
+        .flatten()
+        .copied()
+        .collect();
+    if target.is_empty() {
+        return;
+    }
+    words.retain(|w| match key(w) {
+        (text, _) if text == input => true,
+        (_, Some(entry)) => categories.get(&entry).is_some_and(|c| !c.is_disjoint(&target)),
+        (_, None) => true,
+    });
+}
+
+/// Drop homophones by their JMDict tags
+///
+/// Tags are the JMDict entity codes, e.g. `rK` for a rarely-used kanji form or
+/// `arch` for an archaism. Codes the bundled JMDict does not know (such as
+/// `sK` in older releases) simply never match. The word the input spells
+/// exactly is never filtered out, so searching 牡蛎 still works with `rK`
+/// excluded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordFilter {
+    /// Kanji spelling info to exclude: `rK`, `sK`, `iK`, `oK`, `ateji`...
+    pub exclude_kanji_info: Vec<String>,
+    /// Reading info to exclude: `ik`, `ok`, `gikun`...
+    pub exclude_reading_info: Vec<String>,
+    /// Sense misc tags to exclude: `arch`, `obs`, `vulg`...
+    ///
+    /// A word is dropped only when every sense has one of them.
+    pub exclude_sense_info: Vec<String>,
+    /// Field tags to exclude: `med`, `law`...
+    ///
+    /// A word is dropped only when every sense has one of them.
+    pub exclude_fields: Vec<String>,
+    /// If not empty, keep only words with a sense in one of these fields
+    pub only_fields: Vec<String>,
+    /// If not empty, keep only words with a sense of one of these parts of speech: `n`, `v5u`, `adj-i`...
+    pub only_parts_of_speech: Vec<String>,
+    /// If not empty, keep only words with a sense in one of these categories,
+    /// e.g. [`PartOfSpeech::Noun`] for a word before で
+    pub only_categories: Vec<PartOfSpeech>,
+}
+
+impl WordFilter {
+    pub(crate) fn allows_entry(&self, entry: &jmdict::Entry) -> bool {
+        if !self.exclude_sense_info.is_empty()
+            && entry.senses().all(|s| s.infos().any(|i| contains(&self.exclude_sense_info, i.code())))
+        {
+            return false;
+        }
+        if !self.exclude_fields.is_empty()
+            && entry.senses().all(|s| s.topics().any(|t| contains(&self.exclude_fields, t.code())))
+        {
+            return false;
+        }
+        if !self.only_fields.is_empty()
+            && !entry.senses().any(|s| s.topics().any(|t| contains(&self.only_fields, t.code())))
+        {
+            return false;
+        }
+        if !self.only_parts_of_speech.is_empty()
+            && !entry.senses().any(|s| s.parts_of_speech().any(|p| contains(&self.only_parts_of_speech, p.code())))
+        {
+            return false;
+        }
+        if !self.only_categories.is_empty()
+            && !PartOfSpeech::of_entry(entry).iter().any(|c| self.only_categories.contains(c))
+        {
+            return false;
+        }
+        true
+    }
+
+    pub(crate) fn allows_kanji(&self, kanji: &jmdict::KanjiElement) -> bool {
+        !kanji.infos().any(|i| contains(&self.exclude_kanji_info, i.code()))
+    }
+
+    pub(crate) fn allows_reading(&self, reading: &jmdict::ReadingElement) -> bool {
+        !reading.infos().any(|i| contains(&self.exclude_reading_info, i.code()))
+    }
+}
+
+fn contains(codes: &[String], code: &str) -> bool {
+    codes.iter().any(|c| c == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_with_kanji(text: &str) -> jmdict::Entry {
+        jmdict::entries().find(|e| e.kanji_elements().any(|k| k.text == text)).unwrap()
+    }
+
+    #[test]
+    fn test_tags() {
+        let filter = WordFilter {
+            exclude_kanji_info: vec!["rK".to_string()],
+            exclude_sense_info: vec!["arch".to_string()],
+            ..Default::default()
+        };
+
+        let oyster = entry_with_kanji("牡蠣");
+        let kanji: Vec<_> = oyster.kanji_elements().filter(|k| filter.allows_kanji(k)).map(|k| k.text).collect();
+        assert_eq!(kanji, vec!["牡蠣"]);
+
+        // 支う is an uncommon, archaic entry
+        if cfg!(all(feature = "uncommon", feature = "archaic")) {
+            assert!(!filter.allows_entry(&entry_with_kanji("支う")));
+        }
+        assert!(filter.allows_entry(&entry_with_kanji("買う")));
+
+        let fields = WordFilter { only_fields: vec!["med".to_string()], ..Default::default() };
+        assert!(fields.allows_entry(&entry_with_kanji("診る")));
+        assert!(!fields.allows_entry(&entry_with_kanji("見る")));
+
+        let verbs = WordFilter { only_parts_of_speech: vec!["v5u".to_string()], ..Default::default() };
+        assert!(verbs.allows_entry(&entry_with_kanji("飼う")));
+        assert!(!verbs.allows_entry(&entry_with_kanji("橋")));
+    }
+
+    #[test]
+    fn test_categories() {
+        assert_eq!(PartOfSpeech::of("v5u"), PartOfSpeech::Verb);
+        assert_eq!(PartOfSpeech::of("vs"), PartOfSpeech::Verb);
+        assert_eq!(PartOfSpeech::of("n-adv"), PartOfSpeech::Noun);
+        assert_eq!(PartOfSpeech::of("adj-na"), PartOfSpeech::Adjective);
+        assert_eq!(PartOfSpeech::of("aux-v"), PartOfSpeech::Auxiliary);
+
+        let nouns = WordFilter { only_categories: vec![PartOfSpeech::Noun], ..Default::default() };
+        assert!(nouns.allows_entry(&entry_with_kanji("家庭")));
+        // 仮定 is a noun taking する
+        assert!(nouns.allows_entry(&entry_with_kanji("仮定")));
+        assert!(!nouns.allows_entry(&entry_with_kanji("買う")));
+    }
+}
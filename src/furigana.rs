@@ -0,0 +1,452 @@
+//! Readings aligned to kanji, for display: 構(こう)成(せい).
+//!
+//! JMDict gives whole-word readings only. Each kanji is matched against its
+//! readings from a [`KanjiReadings`] table, allowing rendaku (紙 かみ → がみ in
+//! 手紙) and sokuon (学 がく → がっ in 学校). Kanji the table cannot place are
+//! grouped and share the reading left between their neighbours.
+//!
+//! The built-in table is learned from JMDict itself: single kanji with
+//! okurigana give kun'yomi (買う か), and compounds give on'yomi by agreeing
+//! with each other (構成 こうせい and 構造 こうぞう both start with こう).
+
+use crate::kana_utils::katakana_to_hiragana;
+use crate::{WordFrequency, WordFrequencyWithPitch};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Compounds of more kanji are not learned from
+const MAX_COMPOUND: usize = 4;
+
+/// Longest reading learned for one kanji of a compound, in kana (みずうみ)
+const MAX_PIECE: usize = 4;
+
+/// Rounds of learning from compounds, each using what the last learned
+const MAX_PASSES: usize = 4;
+
+/// Part of a word: kanji with their reading, or kana written as read
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FuriganaSegment {
+    pub text: String,
+    /// Reading to print above `text`, `None` for kana
+    pub reading: Option<String>,
+}
+
+/// Known readings of kanji, in hiragana
+#[derive(Debug, Clone, Default)]
+pub struct KanjiReadings {
+    readings: HashMap<char, Vec<String>>,
+}
+
+impl KanjiReadings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a reading, in hiragana or katakana (on'yomi as KANJIDIC writes them)
+    pub fn insert(&mut self, kanji: char, reading: &str) {
+        let reading = katakana_to_hiragana(reading);
+        let readings = self.readings.entry(kanji).or_default();
+        if !reading.is_empty() && !readings.contains(&reading) {
+            readings.push(reading);
+        }
+    }
+
+    pub fn readings(&self, kanji: char) -> &[String] {
+        self.readings.get(&kanji).map_or(&[], Vec::as_slice)
+    }
+
+    /// Readings learned from JMDict: from words spelled with one kanji (橋
+    /// はし) or one kanji and okurigana (買う か), then from compounds of
+    /// kanji only (構成 こう, せい), see [`KanjiReadings::learn_from_compounds`]
+    pub fn builtin() -> &'static KanjiReadings {
+        static BUILTIN: OnceLock<KanjiReadings> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let mut table = KanjiReadings::new();
+            for entry in jmdict::entries() {
+                for kanji in entry.kanji_elements() {
+                    let mut chars = kanji.text.chars();
+                    let Some(first) = chars.next().filter(|&c| is_kanji(c)) else {
+                        continue;
+                    };
+                    let okurigana = katakana_to_hiragana(chars.as_str());
+                    if okurigana.chars().any(is_kanji) {
+                        continue;
+                    }
+                    for reading in entry.reading_elements() {
+                        if let Some(stem) = reading.text.strip_suffix(okurigana.as_str()) {
+                            table.insert(first, stem);
+                        }
+                    }
+                }
+            }
+            table.learn_from_compounds(jmdict_compounds());
+            table
+        })
+    }
+
+    /// Add readings of kanji in compounds, each given as kanji and reading
+    ///
+    /// Every way of splitting a compound's reading between its kanji is
+    /// scored by how many compounds allow each kanji its piece, readings
+    /// already in the table scoring highest. Compounds whose best split is
+    /// clear and backed by another compound or the table (構成 こう|せい,
+    /// with 構造 and 生成) add its pieces; jukujikun such as 今日 add nothing.
+    /// This repeats while it learns something, so 構造 can be split once 構
+    /// is known from 構成.
+    pub fn learn_from_compounds(&mut self, compounds: impl IntoIterator<Item = (String, String)>) {
+        let compounds: Vec<(Vec<char>, Vec<char>)> = compounds.into_iter()
+            .map(|(text, reading)| (text.chars().collect::<Vec<_>>(), katakana_to_hiragana(&reading).chars().collect::<Vec<_>>()))
+            .filter(|(text, _)| (2..=MAX_COMPOUND).contains(&text.len()) && text.iter().all(|&c| is_kanji(c) && c != '々'))
+            .collect();
+
+        // Compounds in which each kanji could read each piece
+        let mut support: HashMap<char, HashMap<String, usize>> = HashMap::new();
+        for (text, reading) in &compounds {
+            let mut pieces = HashSet::new();
+            for split in splits(text.len(), reading) {
+                pieces.extend(pieces_of(text, reading, &split));
+            }
+            for (kanji, piece) in pieces {
+                *support.entry(kanji).or_default().entry(piece).or_default() += 1;
+            }
+        }
+
+        for _ in 0..MAX_PASSES {
+            let learned = self.best_splits(&compounds, &support);
+            let before = self.readings.values().map(Vec::len).sum::<usize>();
+            for (kanji, piece) in learned {
+                self.insert(kanji, &piece);
+            }
+            if self.readings.values().map(Vec::len).sum::<usize>() == before {
+                break;
+            }
+        }
+    }
+
+    /// Pieces of the compounds whose best split is clear
+    fn best_splits(&self, compounds: &[(Vec<char>, Vec<char>)], support: &HashMap<char, HashMap<String, usize>>) -> Vec<(char, String)> {
+        let mut learned = Vec::new();
+        for (text, reading) in compounds {
+            let score = |split: &[usize]| -> usize {
+                pieces_of(text, reading, split).iter()
+                    .enumerate()
+                    .map(|(i, (kanji, piece))| {
+                        let known = self.readings(*kanji).iter()
+                            .flat_map(|r| variants(r, i > 0))
+                            .any(|form| form.iter().copied().eq(piece.chars()));
+                        let count = support.get(kanji).and_then(|s| s.get(piece)).copied().unwrap_or(0);
+                        if known { compounds.len() + count } else { count }
+                    })
+                    .sum()
+            };
+            let mut scored: Vec<(usize, Vec<usize>)> = splits(text.len(), reading).into_iter().map(|split| (score(&split), split)).collect();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            let clear = scored.get(1).is_none_or(|(second, _)| *second < scored[0].0);
+            if let Some((best, split)) = scored.first()
+                && clear
+                && *best > text.len()
+            {
+                learned.extend(pieces_of(text, reading, split));
+            }
+        }
+        learned
+    }
+}
+
+/// Kanji-only spellings of JMDict words with their readings
+fn jmdict_compounds() -> impl Iterator<Item = (String, String)> {
+    jmdict::entries().flat_map(|entry| {
+        entry.kanji_elements()
+            .filter(|k| k.text.chars().all(is_kanji))
+            .flat_map(move |k| entry.reading_elements().map(move |r| (k.text.to_string(), r.text.to_string())))
+    })
+}
+
+/// Whether a kana can begin a mora's reading (not ゃ, ん, っ, ー...)
+fn starts_mora(c: char) -> bool {
+    !matches!(c, 'ゃ' | 'ゅ' | 'ょ' | 'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'ゎ' | 'ん' | 'っ' | 'ー')
+}
+
+/// Ways to give each of `count` kanji a piece of `reading`, as piece lengths
+fn splits(count: usize, reading: &[char]) -> Vec<Vec<usize>> {
+    if count == 0 {
+        return if reading.is_empty() { vec![Vec::new()] } else { Vec::new() };
+    }
+    let mut all = Vec::new();
+    for len in 1..=MAX_PIECE.min(reading.len()) {
+        if reading.get(len).is_some_and(|&c| !starts_mora(c)) {
+            continue;
+        }
+        for mut rest in splits(count - 1, &reading[len..]) {
+            rest.insert(0, len);
+            all.push(rest);
+        }
+    }
+    all
+}
+
+fn pieces_of(text: &[char], reading: &[char], split: &[usize]) -> Vec<(char, String)> {
+    let mut start = 0;
+    text.iter()
+        .zip(split)
+        .map(|(&kanji, &len)| {
+            start += len;
+            (kanji, reading[start - len..start].iter().collect())
+        })
+        .collect()
+}
+
+fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}' | '々')
+}
+
+/// Voiced forms of a reading's first kana, for kanji inside a word
+fn rendaku(first: char) -> &'static [char] {
+    match first {
+        'か' => &['が'], 'き' => &['ぎ'], 'く' => &['ぐ'], 'け' => &['げ'], 'こ' => &['ご'],
+        'さ' => &['ざ'], 'し' => &['じ'], 'す' => &['ず'], 'せ' => &['ぜ'], 'そ' => &['ぞ'],
+        'た' => &['だ'], 'ち' => &['ぢ', 'じ'], 'つ' => &['づ', 'ず'], 'て' => &['で'], 'と' => &['ど'],
+        'は' => &['ば', 'ぱ'], 'ひ' => &['び', 'ぴ'], 'ふ' => &['ぶ', 'ぷ'], 'へ' => &['べ', 'ぺ'], 'ほ' => &['ぼ', 'ぽ'],
+        _ => &[],
+    }
+}
+
+/// A reading with the rendaku and sokuon forms it can take, longest first
+fn variants(reading: &str, inside_word: bool) -> Vec<Vec<char>> {
+    let chars: Vec<char> = reading.chars().collect();
+    let mut forms = vec![chars.clone()];
+    if inside_word && let Some(&first) = chars.first() {
+        for &voiced in rendaku(first) {
+            let mut form = chars.clone();
+            form[0] = voiced;
+            forms.push(form);
+        }
+    }
+    // がく → がっ (学校), いち → いっ (一杯)
+    if chars.len() > 1 && matches!(chars.last(), Some('つ' | 'く' | 'ち' | 'き')) {
+        for form in forms.clone() {
+            let mut form = form;
+            *form.last_mut().unwrap() = 'っ';
+            forms.push(form);
+        }
+    }
+    forms.sort_by_key(|f| std::cmp::Reverse(f.len()));
+    forms
+}
+
+/// Align a reading to a spelling with the built-in table, see [`KanjiReadings::builtin`]
+pub fn align_furigana(text: &str, reading: &str) -> Vec<FuriganaSegment> {
+    align_furigana_with(text, reading, KanjiReadings::builtin())
+}
+
+/// Align a reading to a spelling, one segment per kanji where the table allows
+///
+/// If kana in the spelling do not match the reading, the whole word comes
+/// back as one segment.
+pub fn align_furigana_with(text: &str, reading: &str, table: &KanjiReadings) -> Vec<FuriganaSegment> {
+    let text_chars: Vec<char> = text.chars().collect();
+    let reading_chars: Vec<char> = katakana_to_hiragana(reading).chars().collect();
+    let segments = align(&text_chars, &reading_chars, None, table).unwrap_or_else(|| {
+        vec![FuriganaSegment { text: text.to_string(), reading: Some(reading_chars.iter().collect()) }]
+    });
+
+    // Merge neighbouring kana, and drop readings that only repeat the text
+    let mut merged: Vec<FuriganaSegment> = Vec::new();
+    for mut segment in segments {
+        if segment.reading.as_deref() == Some(katakana_to_hiragana(&segment.text).as_str()) {
+            segment.reading = None;
+        }
+        match merged.last_mut() {
+            Some(last) if last.reading.is_none() && segment.reading.is_none() => last.text.push_str(&segment.text),
+            _ => merged.push(segment),
+        }
+    }
+    merged
+}
+
+fn align(text: &[char], reading: &[char], previous: Option<char>, table: &KanjiReadings) -> Option<Vec<FuriganaSegment>> {
+    let Some(&c) = text.first() else {
+        return reading.is_empty().then(Vec::new);
+    };
+
+    if !is_kanji(c) {
+        let kana = katakana_to_hiragana(&c.to_string());
+        if reading.first().map(|r| r.to_string()) != Some(kana) {
+            return None;
+        }
+        let mut rest = align(&text[1..], &reading[1..], Some(c), table)?;
+        rest.insert(0, FuriganaSegment { text: c.to_string(), reading: None });
+        return Some(rest);
+    }
+
+    // 々 repeats the kanji before it
+    let lookup = if c == '々' { previous.unwrap_or(c) } else { c };
+    for form in table.readings(lookup).iter().flat_map(|r| variants(r, previous.is_some())) {
+        if reading.starts_with(&form)
+            && let Some(mut rest) = align(&text[1..], &reading[form.len()..], Some(c), table)
+        {
+            rest.insert(0, FuriganaSegment { text: c.to_string(), reading: Some(form.iter().collect()) });
+            return Some(rest);
+        }
+    }
+
+    // Unknown kanji: the run of kanji takes whatever reading the kana after it leave
+    let run = text.iter().take_while(|&&k| is_kanji(k)).count();
+    for end in run..=reading.len() {
+        if let Some(mut rest) = align(&text[run..], &reading[end..], Some(text[run - 1]), table) {
+            rest.insert(0, FuriganaSegment {
+                text: text[..run].iter().collect(),
+                reading: Some(reading[..end].iter().collect()),
+            });
+            return Some(rest);
+        }
+    }
+    None
+}
+
+/// HTML with `<ruby>` around kanji: `<ruby>構<rt>こう</rt></ruby>`
+pub fn ruby_html(segments: &[FuriganaSegment]) -> String {
+    let mut html = String::new();
+    for segment in segments {
+        match &segment.reading {
+            Some(reading) => html.push_str(&format!("<ruby>{}<rt>{}</rt></ruby>", escape_html(&segment.text), escape_html(reading))),
+            None => html.push_str(&escape_html(&segment.text)),
+        }
+    }
+    html
+}
+
+/// Anki-style brackets per kanji: `買[か]う`, with a space before kanji that follow other text (`構[こう] 成[せい]`)
+pub fn bracket_notation(segments: &[FuriganaSegment]) -> String {
+    let mut notation = String::new();
+    for segment in segments {
+        match &segment.reading {
+            Some(reading) => push_bracketed(&mut notation, &segment.text, reading),
+            None => notation.push_str(&segment.text),
+        }
+    }
+    notation
+}
+
+/// Word-level brackets: each run of kanji under one reading, `構成[こうせい]する`
+pub fn word_bracket_notation(segments: &[FuriganaSegment]) -> String {
+    let mut notation = String::new();
+    let mut run: Option<(String, String)> = None;
+    for segment in segments {
+        match &segment.reading {
+            Some(reading) => {
+                let (text, kana) = run.get_or_insert_with(Default::default);
+                text.push_str(&segment.text);
+                kana.push_str(reading);
+            }
+            None => {
+                if let Some((text, kana)) = run.take() {
+                    push_bracketed(&mut notation, &text, &kana);
+                }
+                notation.push_str(&segment.text);
+            }
+        }
+    }
+    if let Some((text, kana)) = run {
+        push_bracketed(&mut notation, &text, &kana);
+    }
+    notation
+}
+
+fn push_bracketed(notation: &mut String, text: &str, reading: &str) {
+    if !notation.is_empty() {
+        notation.push(' ');
+    }
+    notation.push_str(&format!("{text}[{reading}]"));
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl WordFrequency {
+    /// The word's reading aligned to its kanji, see [`align_furigana`]
+    pub fn furigana(&self) -> Vec<FuriganaSegment> {
+        align_furigana(&self.text, &self.reading)
+    }
+}
+
+impl WordFrequencyWithPitch {
+    /// The word's reading aligned to its kanji, see [`align_furigana`]
+    pub fn furigana(&self) -> Vec<FuriganaSegment> {
+        align_furigana(&self.text, &self.reading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(pairs: &[(&str, Option<&str>)]) -> Vec<FuriganaSegment> {
+        pairs.iter()
+            .map(|&(text, reading)| FuriganaSegment { text: text.to_string(), reading: reading.map(str::to_string) })
+            .collect()
+    }
+
+    fn table(readings: &[(char, &str)]) -> KanjiReadings {
+        let mut table = KanjiReadings::new();
+        for &(kanji, reading) in readings {
+            table.insert(kanji, reading);
+        }
+        table
+    }
+
+    #[test]
+    fn test_okurigana() {
+        assert_eq!(align_furigana("買う", "かう"), segments(&[("買", Some("か")), ("う", None)]));
+        assert_eq!(align_furigana("食べる", "たべる"), segments(&[("食", Some("た")), ("べる", None)]));
+        assert_eq!(align_furigana("タピオカ", "たぴおか"), segments(&[("タピオカ", None)]));
+    }
+
+    #[test]
+    fn test_builtin_compounds() {
+        assert_eq!(align_furigana("構成", "こうせい"), segments(&[("構", Some("こう")), ("成", Some("せい"))]));
+        assert_eq!(align_furigana("公正", "こうせい"), segments(&[("公", Some("こう")), ("正", Some("せい"))]));
+        assert_eq!(align_furigana("学校", "がっこう"), segments(&[("学", Some("がっ")), ("校", Some("こう"))]));
+        assert_eq!(align_furigana("手紙", "てがみ"), segments(&[("手", Some("て")), ("紙", Some("がみ"))]));
+    }
+
+    #[test]
+    fn test_learn_from_compounds() {
+        let mut readings = table(&[('日', "ひ")]);
+        let compounds = [("構成", "こうせい"), ("構造", "こうぞう"), ("生成", "せいせい"), ("今日", "きょう")];
+        readings.learn_from_compounds(compounds.iter().map(|&(t, r)| (t.to_string(), r.to_string())));
+        assert_eq!(readings.readings('構'), ["こう"]);
+        assert_eq!(readings.readings('成'), ["せい"]);
+        assert_eq!(readings.readings('造'), ["ぞう"]);
+        // 今日 splits no way better than another
+        assert!(readings.readings('今').is_empty());
+        assert_eq!(readings.readings('日'), ["ひ"]);
+    }
+
+    #[test]
+    fn test_table_alignment() {
+        let readings = table(&[('構', "コウ"), ('成', "セイ"), ('手', "て"), ('紙', "かみ"), ('学', "ガク"), ('校', "コウ"), ('人', "ひと")]);
+        assert_eq!(align_furigana_with("構成", "こうせい", &readings), segments(&[("構", Some("こう")), ("成", Some("せい"))]));
+        // Rendaku and sokuon
+        assert_eq!(align_furigana_with("手紙", "てがみ", &readings)[1], segments(&[("紙", Some("がみ"))])[0]);
+        assert_eq!(align_furigana_with("学校", "がっこう", &readings)[0], segments(&[("学", Some("がっ"))])[0]);
+        assert_eq!(align_furigana_with("人々", "ひとびと", &readings)[1], segments(&[("々", Some("びと"))])[0]);
+
+        // Kanji missing from the table share the rest of the reading
+        assert_eq!(align_furigana_with("構造", "こうぞう", &readings), segments(&[("構", Some("こう")), ("造", Some("ぞう"))]));
+        assert_eq!(align_furigana_with("取引", "とりひき", &readings), segments(&[("取引", Some("とりひき"))]));
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let words = segments(&[("構", Some("こう")), ("成", Some("せい")), ("する", None)]);
+        assert_eq!(ruby_html(&words), "<ruby>構<rt>こう</rt></ruby><ruby>成<rt>せい</rt></ruby>する");
+        assert_eq!(bracket_notation(&words), "構[こう] 成[せい]する");
+        assert_eq!(bracket_notation(&segments(&[("構成", Some("こうせい"))])), "構成[こうせい]");
+        assert_eq!(word_bracket_notation(&words), "構成[こうせい]する");
+        let mixed = segments(&[("お", None), ("茶", Some("ちゃ")), ("の", None), ("間", Some("ま"))]);
+        assert_eq!(word_bracket_notation(&mixed), "お 茶[ちゃ]の 間[ま]");
+    }
+}
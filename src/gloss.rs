@@ -0,0 +1,245 @@
+//! Meanings of words, and reverse lookup from an English meaning.
+//!
+//! "composition" finds 構成 through its gloss, then runs [`find_with_nhk`] on
+//! it, so the homophones of the word for a meaning come out with their pitch.
+//!
+//! Meanings come in the first of the caller's languages an entry has glosses
+//! in (JMDict codes: `ger`, `fre`, `rus`, `spa`...), else in English. Languages
+//! other than English need the `all-glosses` feature, on by default.
+
+use crate::{FindWithNhkResult, MatchKind, WordFrequency, WordFrequencyWithPitch, find_with_nhk};
+use jmdict::Enum;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// A JMDict entry with a gloss matching the query, and the homophones of its word
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GlossMatch {
+    /// The entry's first spelling and reading
+    pub word: WordFrequencyWithPitch,
+    /// The gloss that matched
+    pub gloss: String,
+    /// Byte range of the query in `gloss`
+    pub matched: Range<usize>,
+    /// [`find_with_nhk`] of `word.text`
+    pub homophones: FindWithNhkResult,
+    /// Meanings of the entry in the requested languages
+    pub meanings: Meanings,
+}
+
+/// Glosses of one entry in one language
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Meanings {
+    /// Language of `senses`: the first requested language the entry has, or
+    /// `eng`; `None` if it has no glosses at all
+    pub language: Option<String>,
+    /// Glosses of each sense in that language
+    pub senses: Vec<Vec<String>>,
+    /// Requested languages the entry has no glosses in, in request order
+    pub missing: Vec<String>,
+}
+
+/// Meanings of an entry in the first of `languages` it has, falling back to English
+///
+/// Codes this build has no glosses for (without `all-glosses`) are reported
+/// missing like any other.
+pub fn entry_meanings(entry: &jmdict::Entry, languages: &[&str]) -> Meanings {
+    let senses_in = |language: &str| -> Vec<Vec<String>> {
+        entry.senses()
+            .map(|s| s.glosses().filter(|g| g.language.code() == language).map(|g| g.text.to_string()).collect::<Vec<_>>())
+            .filter(|glosses| !glosses.is_empty())
+            .collect()
+    };
+
+    let mut missing = Vec::new();
+    let mut chosen = None;
+    for &language in languages {
+        let senses = senses_in(language);
+        if senses.is_empty() {
+            if !missing.iter().any(|m| m == language) {
+                missing.push(language.to_string());
+            }
+        } else if chosen.is_none() {
+            chosen = Some((language.to_string(), senses));
+        }
+    }
+    let chosen = chosen.or_else(|| {
+        let english = senses_in("eng");
+        (!english.is_empty()).then(|| ("eng".to_string(), english))
+    });
+
+    match chosen {
+        Some((language, senses)) => Meanings { language: Some(language), senses, missing },
+        None => Meanings { language: None, senses: Vec::new(), missing },
+    }
+}
+
+fn entry_by_number(number: u32) -> Option<jmdict::Entry> {
+    static ENTRIES: OnceLock<HashMap<u32, jmdict::Entry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| jmdict::entries().map(|e| (e.number, e)).collect())
+        .get(&number)
+        .copied()
+}
+
+impl WordFrequency {
+    /// Meanings of the word's JMDict entry, see [`entry_meanings`]; `None` for
+    /// names and user words
+    pub fn meanings(&self, languages: &[&str]) -> Option<Meanings> {
+        entry_by_number(self.jmdict_entry?).map(|e| entry_meanings(&e, languages))
+    }
+}
+
+impl WordFrequencyWithPitch {
+    /// Meanings of the word's JMDict entry, see [`entry_meanings`]; `None` for
+    /// names and user words
+    pub fn meanings(&self, languages: &[&str]) -> Option<Meanings> {
+        entry_by_number(self.jmdict_entry?).map(|e| entry_meanings(&e, languages))
+    }
+}
+
+impl GlossMatch {
+    /// `gloss` with the matching words wrapped, e.g. `highlighted("<b>", "</b>")`
+    pub fn highlighted(&self, before: &str, after: &str) -> String {
+        format!(
+            "{}{before}{}{after}{}",
+            &self.gloss[..self.matched.start],
+            &self.gloss[self.matched.clone()],
+            &self.gloss[self.matched.end..]
+        )
+    }
+}
+
+/// Where `query` occurs in `gloss` as whole words, ignoring ASCII case
+fn find_words(gloss: &str, query: &str) -> Option<Range<usize>> {
+    let haystack = gloss.to_ascii_lowercase();
+    let query = query.to_ascii_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    haystack.match_indices(&query)
+        .map(|(start, _)| start..start + query.len())
+        .find(|range| !is_word(haystack[..range.start].chars().next_back()) && !is_word(haystack[range.end..].chars().next()))
+}
+
+/// Entries with an English gloss containing `query` as whole words, each with its homophones
+///
+/// "composition" matches "composition" and "(musical) composition", not
+/// "decomposition". Entries whose gloss is exactly the query come first,
+/// then the most frequent. Each entry is listed once, with its first
+/// matching gloss and its English meanings. Returns at most `limit` entries:
+/// homophones are only looked up for those, so a common word such as "make"
+/// stays fast.
+pub fn find_by_gloss(query: &str, limit: usize) -> Vec<GlossMatch> {
+    find_by_gloss_in(query, &[], limit)
+}
+
+/// [`find_by_gloss`] with meanings in the first of `languages` each entry has
+pub fn find_by_gloss_in(query: &str, languages: &[&str], limit: usize) -> Vec<GlossMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for entry in jmdict::entries() {
+        let found = entry.senses()
+            .flat_map(|s| s.glosses())
+            .filter(|g| g.language.code() == "eng")
+            .filter_map(|g| find_words(g.text, query).map(|range| (g.text, range)))
+            .min_by_key(|(text, _)| text.len() != query.len());
+        let Some((gloss, matched)) = found else {
+            continue;
+        };
+        let Some(reading) = entry.reading_elements().next() else {
+            continue;
+        };
+        let word = match entry.kanji_elements().next() {
+            Some(kanji) => WordFrequencyWithPitch::jmdict(entry.number, kanji.text, reading.text, &kanji.priority, MatchKind::KanjiElement),
+            None => WordFrequencyWithPitch::jmdict(entry.number, reading.text, reading.text, &reading.priority, MatchKind::Reading),
+        };
+        let exact = gloss.len() == query.len();
+        matches.push((exact, word, gloss, matched, entry));
+    }
+
+    matches.sort_by_key(|(exact, word, ..)| (!exact, std::cmp::Reverse(word.frequency_score)));
+    matches.truncate(limit);
+
+    // Each find_with_nhk scans the dictionary, so run it once per spelling
+    let mut homophones_of: HashMap<String, FindWithNhkResult> = HashMap::new();
+    matches.into_iter()
+        .map(|(_, word, gloss, matched, entry)| {
+            let homophones = homophones_of.entry(word.text.clone())
+                .or_insert_with(|| find_with_nhk(&word.text))
+                .clone();
+            let meanings = entry_meanings(&entry, languages);
+            GlossMatch { word, gloss: gloss.to_string(), matched, homophones, meanings }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_by_gloss() {
+        let results = find_by_gloss("Composition", 10);
+        let kousei = results.iter().find(|m| m.word.text == "構成").unwrap();
+        assert_eq!(kousei.gloss, "composition");
+        assert_eq!(kousei.highlighted("<b>", "</b>"), "<b>composition</b>");
+        let FindWithNhkResult::UniqueMatch { true_homophones, .. } = &kousei.homophones else {
+            panic!("expected homophones of 構成");
+        };
+        assert!(true_homophones.iter().any(|w| w.text == "公正"));
+
+        let answers = find_by_gloss("answer", 10);
+        assert!(answers.len() >= 2);
+        for m in &answers {
+            assert_eq!(m.homophones, find_with_nhk(&m.word.text), "{}", m.word.text);
+        }
+
+        assert_eq!(find_by_gloss("answer", 1), answers[..1]);
+        assert!(find_by_gloss("answer", 0).is_empty());
+
+        assert!(find_by_gloss("compos", 10).is_empty());
+        assert!(find_by_gloss(" ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_meanings() {
+        let kousei = jmdict::entries().find(|e| e.kanji_elements().any(|k| k.text == "構成")).unwrap();
+
+        let has_gloss = |meanings: &Meanings, gloss: &str| meanings.senses.iter().flatten().any(|g| g == gloss);
+
+        let english = entry_meanings(&kousei, &[]);
+        assert_eq!(english.language.as_deref(), Some("eng"));
+        assert!(has_gloss(&english, "composition"));
+        assert!(english.missing.is_empty());
+
+        if cfg!(feature = "all-glosses") {
+            let german = entry_meanings(&kousei, &["swe", "ger", "fre"]);
+            assert_eq!(german.language.as_deref(), Some("ger"));
+            assert!(has_gloss(&german, "Zusammensetzung"));
+            assert!(!has_gloss(&german, "composition"));
+            assert_eq!(german.missing, vec!["swe".to_string()]);
+        }
+
+        // Nothing requested is there: English, with every language reported
+        let fallback = entry_meanings(&kousei, &["swe", "xxx"]);
+        assert_eq!(fallback.language.as_deref(), Some("eng"));
+        assert_eq!(fallback.missing, vec!["swe".to_string(), "xxx".to_string()]);
+
+        let word = crate::find("構成").into_iter().find(|w| w.text == "構成").unwrap();
+        assert_eq!(word.meanings(&[]), Some(english));
+        let matches = find_by_gloss_in("composition", &["swe"], 10);
+        assert_eq!(matches[0].meanings.missing, vec!["swe".to_string()]);
+    }
+
+    #[test]
+    fn test_find_words() {
+        assert_eq!(find_words("(musical) composition", "composition"), Some(10..21));
+        assert_eq!(find_words("decomposition", "composition"), None);
+        assert_eq!(find_words("to buy", "Buy"), Some(3..6));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::kana_utils::katakana_to_hiragana;
+use crate::pitch::{AccentType, accent_type, mora_count};
+use crate::{FindOptions, MatchKind, PitchSource, WordFrequencyWithPitch, distinct_word_count, group_by_entry};
+use std::collections::{BTreeMap, HashSet};
+
+/// All words in the dictionary sharing one reading
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HomophoneGroup {
+    /// Reading in hiragana
+    pub reading: String,
+    /// Words with this reading, most common first
+    pub words: Vec<WordFrequencyWithPitch>,
+}
+
+/// Enumerate every homophone group in JMDict
+///
+/// A group is every word whose reading normalizes to the same hiragana, so
+/// katakana words join kanji words (ソーセージ and 双生児). Readings of only
+/// one JMDict entry are left out, even if it has several spellings (綺麗 and
+/// 奇麗). Groups are ordered by reading.
+pub fn homophone_groups() -> Vec<HomophoneGroup> {
+    let mut reading_to_words: BTreeMap<String, Vec<WordFrequencyWithPitch>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+
+    for entry in jmdict::entries() {
+        for reading in entry.reading_elements() {
+            let key = katakana_to_hiragana(reading.text);
+
+            for (text, priority) in FindOptions::default().spellings(&entry, &reading) {
+                if seen.insert((text, reading.text)) {
+                    reading_to_words.entry(key.clone()).or_default()
+                        .push(WordFrequencyWithPitch::jmdict(entry.number, text, reading.text, &priority, MatchKind::SharedReading));
+                }
+            }
+        }
+    }
+
+    reading_to_words.into_iter()
+        .filter(|(_, words)| distinct_word_count(words.iter()) > 1)
+        .map(|(reading, mut words)| {
+            words.sort_by_key(|w| std::cmp::Reverse(w.frequency_score));
+            HomophoneGroup { reading, words }
+        })
+        .collect()
+}
+
+/// Words with one reading and different NHK accents, such as 雨[1] and 飴[0]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MinimalPairSet {
+    /// Reading in hiragana
+    pub reading: String,
+    /// One word per JMDict entry, other spellings in `variants`, most common first
+    pub words: Vec<WordFrequencyWithPitch>,
+    /// Sum of the words' frequency scores
+    pub frequency_score: u32,
+}
+
+/// Which minimal pairs [`pitch_minimal_pairs`] returns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinimalPairFilter {
+    /// If not empty, keep only sets with a word of one of these accent types
+    pub accent_types: Vec<AccentType>,
+    /// If not empty, keep only readings with one of these mora counts
+    pub mora_counts: Vec<usize>,
+    /// Leave out words not marked common in JMDict
+    pub common_only: bool,
+}
+
+/// Every reading whose words differ in pitch accent, most frequent sets first
+///
+/// Only words with NHK data take part, compared by their first accent. A set
+/// needs two or more accents among its words; words sharing an accent all stay.
+pub fn pitch_minimal_pairs(filter: &MinimalPairFilter) -> Vec<MinimalPairSet> {
+    let mut sets: Vec<MinimalPairSet> = homophone_groups().into_iter()
+        .filter(|group| filter.mora_counts.is_empty() || filter.mora_counts.contains(&mora_count(&group.reading)))
+        .filter_map(|group| {
+            let words: Vec<WordFrequencyWithPitch> = group_by_entry(group.words).into_iter()
+                .filter(|w| w.pitch_source == PitchSource::Nhk && (w.is_common || !filter.common_only))
+                .collect();
+
+            let accents: HashSet<u8> = words.iter().map(|w| w.pitch_accent[0]).collect();
+            if accents.len() < 2 {
+                return None;
+            }
+            let morae = mora_count(&group.reading);
+            if !filter.accent_types.is_empty()
+                && !accents.iter().any(|&a| filter.accent_types.contains(&accent_type(a, morae)))
+            {
+                return None;
+            }
+
+            let frequency_score = words.iter().map(|w| w.frequency_score).sum();
+            Some(MinimalPairSet { reading: group.reading, words, frequency_score })
+        })
+        .collect();
+    sets.sort_by_key(|s| std::cmp::Reverse(s.frequency_score));
+    sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_homophone_groups() {
+        let groups = homophone_groups();
+
+        let kousei = groups.iter().find(|g| g.reading == "こうせい").unwrap();
+        assert!(kousei.words.iter().any(|w| w.text == "構成"));
+        assert!(kousei.words.iter().any(|w| w.text == "公正"));
+        for i in 1..kousei.words.len() {
+            assert!(kousei.words[i - 1].frequency_score >= kousei.words[i].frequency_score);
+        }
+
+        // Katakana words are grouped under their hiragana reading (with the
+        // uncommon 双生児)
+        if cfg!(feature = "uncommon") {
+            let souseiji = groups.iter().find(|g| g.reading == "そうせいじ").unwrap();
+            assert!(souseiji.words.iter().any(|w| w.text == "ソーセージ"));
+        }
+
+        // Words without homophones are not groups
+        assert!(!groups.iter().any(|g| g.reading == "ちゅうごくご"));
+        assert!(!groups.iter().any(|g| g.reading == "きれい"));
+    }
+
+    #[test]
+    fn test_pitch_minimal_pairs() {
+        let sets = pitch_minimal_pairs(&MinimalPairFilter::default());
+        let ame = sets.iter().find(|s| s.reading == "あめ").unwrap();
+        let texts: Vec<&str> = ame.words.iter().map(|w| w.text.as_str()).collect();
+        assert!(texts.contains(&"雨") && texts.contains(&"飴"));
+        for i in 1..sets.len() {
+            assert!(sets[i - 1].frequency_score >= sets[i].frequency_score);
+        }
+
+        // 牡蠣 and 牡蛎 are one word, so かき is still one 牡蠣/柿 set
+        let kaki = sets.iter().find(|s| s.reading == "かき").unwrap();
+        assert_eq!(kaki.words.len(), 2);
+
+        // かう has 買う[0] and 飼う[1]; 支う has no NHK data
+        let kau = sets.iter().find(|s| s.reading == "かう").unwrap();
+        assert!(kau.words.iter().all(|w| w.text != "支う"));
+
+        let odaka = pitch_minimal_pairs(&MinimalPairFilter { accent_types: vec![AccentType::Odaka], ..Default::default() });
+        assert!(odaka.iter().any(|s| s.reading == "はし"));
+        assert!(!odaka.iter().any(|s| s.reading == "あめ"));
+
+        let three = pitch_minimal_pairs(&MinimalPairFilter { mora_counts: vec![3], ..Default::default() });
+        assert!(three.iter().all(|s| mora_count(&s.reading) == 3));
+    }
+}
@@ -57,6 +57,22 @@ pub fn contains_katakana(s: &str) -> bool {
     s.chars().any(|c| matches!(c, 'ア'..='ン' | 'ヴ' | 'ァ'..='ヶ'))
 }
 
+/// Check if a character is kana, kanji or a Japanese-specific mark (ー, 々, 〆, ヶ)
+pub fn is_japanese(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{309F}'   // Hiragana
+            | '\u{30A0}'..='\u{30FF}' // Katakana, including ー and ・
+            | '\u{31F0}'..='\u{31FF}' // Katakana phonetic extensions
+            | '\u{FF66}'..='\u{FF9F}' // Halfwidth katakana
+            | '\u{3400}'..='\u{4DBF}' // CJK extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK unified ideographs
+            | '\u{F900}'..='\u{FAFF}' // CJK compatibility ideographs
+            | '\u{20000}'..='\u{2FA1F}' // CJK extensions B-F and supplement
+            | '々' | '〆' | '〇'
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,4 +108,12 @@ mod tests {
         assert!(!contains_katakana("漢字"));
         assert!(!contains_katakana("hello"));
     }
+
+    #[test]
+    fn test_is_japanese() {
+        assert!("構成こうせいコウセイ々ー".chars().all(is_japanese));
+        assert!(!is_japanese('a'));
+        assert!(!is_japanese('1'));
+        assert!(!is_japanese(' '));
+    }
 }
\ No newline at end of file
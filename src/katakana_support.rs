@@ -1,11 +1,13 @@
 use crate::kana_utils::{katakana_to_hiragana, contains_katakana};
-use crate::{WordFrequency, calculate_frequency_score};
-use std::collections::HashMap;
+use crate::filter::retain_same_part_of_speech;
+use crate::{FindOptions, MatchKind, WordFrequency, WordSource, group_by_entry, reading_match_kind};
 
 /// Enhanced find function that properly handles katakana input
-pub fn find_with_katakana_support(word: &str) -> Vec<WordFrequency> {
-    let mut homophones = Vec::new();
-    let mut reading_to_words: HashMap<String, Vec<(String, u32, bool)>> = HashMap::new();
+///
+/// Also returns whether anything matched in the dictionary, as opposed to only
+/// the katakana input itself being listed.
+pub fn find_with_katakana_support(word: &str, options: &FindOptions) -> (Vec<WordFrequency>, bool) {
+    let mut candidates: Vec<WordFrequency> = Vec::new();
     
     // Convert katakana to hiragana for searching, since JMDict stores readings in hiragana
     let search_word = if contains_katakana(word) {
@@ -35,11 +37,7 @@ pub fn find_with_katakana_support(word: &str) -> Vec<WordFrequency> {
                         target_readings.push(reading.text.to_string());
                     }
                     
-                    let freq_score = calculate_frequency_score(&reading.priority);
-                    let key = reading.text.to_string();
-                    reading_to_words.entry(key)
-                        .or_insert_with(Vec::new)
-                        .push((kanji.text.to_string(), freq_score, reading.priority.is_common()));
+                    candidates.push(WordFrequency::jmdict(entry.number, kanji.text, reading.text, &reading.priority, MatchKind::KanjiElement));
                 }
             }
         }
@@ -51,27 +49,58 @@ pub fn find_with_katakana_support(word: &str) -> Vec<WordFrequency> {
                     target_readings.push(reading.text.to_string());
                 }
                 
-                let freq_score = calculate_frequency_score(&reading.priority);
-                let key = reading.text.to_string();
+                let match_kind = if reading.text == original_word {
+                    MatchKind::Reading
+                } else {
+                    MatchKind::KatakanaConversion
+                };
                 
                 // For kana-only entries
                 if entry.kanji_elements().count() == 0 {
-                    reading_to_words.entry(key)
-                        .or_insert_with(Vec::new)
-                        .push((reading.text.to_string(), freq_score, reading.priority.is_common()));
-                } else {
+                    // The input spells this word exactly, so it is never filtered out
+                    if reading.text == original_word || options.allows(&entry, &reading) {
+                        candidates.push(WordFrequency::jmdict(entry.number, reading.text, reading.text, &reading.priority, match_kind));
+                    }
+                } else if options.allows(&entry, &reading) {
                     // Add all kanji forms with this reading
-                    for kanji in entry.kanji_elements() {
-                        let kanji_freq_score = calculate_frequency_score(&kanji.priority);
-                        reading_to_words.entry(key.clone())
-                            .or_insert_with(Vec::new)
-                            .push((kanji.text.to_string(), kanji_freq_score, kanji.priority.is_common()));
+                    for kanji in entry.kanji_elements().filter(|k| options.allows_kanji(k)) {
+                        candidates.push(WordFrequency::jmdict(entry.number, kanji.text, reading.text, &kanji.priority, match_kind));
                     }
                 }
             }
         }
     }
     
+    // Names spelled like the input; their readings join the search
+    if let Some(names) = options.names {
+        for name in names.with_text(original_word) {
+            if !target_readings.contains(&name.reading) {
+                target_readings.push(name.reading.clone());
+            }
+            let match_kind = if name.text == name.reading {
+                MatchKind::Reading
+            } else {
+                MatchKind::KanjiElement
+            };
+            candidates.push(WordFrequency::name(name, match_kind));
+        }
+    }
+    
+    // User entries spelled like the input
+    if let Some(user) = options.user_dictionary {
+        for entry in user.with_text(original_word) {
+            if !target_readings.contains(&entry.reading) {
+                target_readings.push(entry.reading.clone());
+            }
+            let match_kind = if entry.text == entry.reading {
+                MatchKind::Reading
+            } else {
+                MatchKind::KanjiElement
+            };
+            candidates.push(WordFrequency::user(entry, match_kind));
+        }
+    }
+    
     // Second pass: collect all words with the same readings as our target word
     // Now we need to normalize readings to handle long vowels properly
     if !target_readings.is_empty() {
@@ -86,30 +115,33 @@ pub fn find_with_katakana_support(word: &str) -> Vec<WordFrequency> {
                 let normalized_reading = katakana_to_hiragana(reading.text);
                 
                 // Check if this normalized reading matches any of our targets
-                if normalized_targets.contains(&normalized_reading) {
-                    let freq_score = calculate_frequency_score(&reading.priority);
-                    // Use the original reading for storage
-                    let key = reading.text.to_string();
-                    
-                    if entry.kanji_elements().count() == 0 {
-                        // Kana-only entry
-                        reading_to_words.entry(key)
-                            .or_insert_with(Vec::new)
-                            .push((reading.text.to_string(), freq_score, reading.priority.is_common()));
-                    } else {
-                        // Add all kanji forms
-                        for kanji in entry.kanji_elements() {
-                            let kanji_freq_score = calculate_frequency_score(&kanji.priority);
-                            reading_to_words.entry(key.clone())
-                                .or_insert_with(Vec::new)
-                                .push((kanji.text.to_string(), kanji_freq_score, kanji.priority.is_common()));
-                        }
+                if normalized_targets.contains(&normalized_reading) && options.allows(&entry, &reading) {
+                    for (text, priority) in options.spellings(&entry, &reading) {
+                        candidates.push(WordFrequency::jmdict(entry.number, text, reading.text, &priority, MatchKind::SharedReading));
                     }
                 }
             }
         }
+        
+        if let Some(names) = options.names {
+            for reading in &normalized_targets {
+                for name in names.with_reading(reading) {
+                    candidates.push(WordFrequency::name(name, reading_match_kind(&name.reading, original_word, hiragana_word)));
+                }
+            }
+        }
+        
+        if let Some(user) = options.user_dictionary {
+            for reading in &normalized_targets {
+                for entry in user.with_reading(reading) {
+                    candidates.push(WordFrequency::user(entry, reading_match_kind(&entry.reading, original_word, hiragana_word)));
+                }
+            }
+        }
     }
     
+    let found_in_dictionary = !candidates.is_empty();
+    
     // If input was katakana, also include the katakana word itself
     if contains_katakana(original_word) {
         // Check if the katakana word exists in JMDict (like Χ for Chi)
@@ -117,45 +149,57 @@ pub fn find_with_katakana_support(word: &str) -> Vec<WordFrequency> {
             for kanji in entry.kanji_elements() {
                 if kanji.text == original_word {
                     // Found the katakana entry
-                    let freq_score = calculate_frequency_score(&kanji.priority);
-                    reading_to_words.entry(hiragana_word.to_string())
-                        .or_insert_with(Vec::new)
-                        .push((original_word.to_string(), freq_score, kanji.priority.is_common()));
+                    candidates.push(WordFrequency::jmdict(entry.number, original_word, hiragana_word, &kanji.priority, MatchKind::KanjiElement));
                     break;
                 }
             }
         }
         
         // Also add the katakana itself even if not in JMDict
-        let has_katakana_entry = reading_to_words.get(hiragana_word)
-            .map(|words| words.iter().any(|(text, _, _)| text == original_word))
-            .unwrap_or(false);
+        let has_katakana_entry = candidates.iter()
+            .any(|w| w.text == original_word && w.reading == hiragana_word);
             
         if !has_katakana_entry {
-            reading_to_words.entry(hiragana_word.to_string())
-                .or_insert_with(Vec::new)
-                .push((original_word.to_string(), 0, false));
+            candidates.push(WordFrequency {
+                text: original_word.to_string(),
+                reading: hiragana_word.to_string(),
+                frequency_score: 0,
+                is_common: false,
+                match_kind: MatchKind::KatakanaSelfEntry,
+                source: WordSource::Jmdict,
+                jmdict_entry: None,
+                variants: Vec::new(),
+                meanings: None,
+            });
         }
     }
     
-    // Convert to output format and deduplicate
+    // Deduplicate, keeping the first (most direct) match of each word
     let mut seen = std::collections::HashSet::new();
-    for (reading, words) in reading_to_words {
-        for (text, freq_score, is_common) in words {
-            let key = (text.clone(), reading.clone());
-            if seen.insert(key) {
-                homophones.push(WordFrequency {
-                    text,
-                    reading: reading.clone(),
-                    frequency_score: freq_score,
-                    is_common,
-                });
-            }
+    let mut homophones: Vec<WordFrequency> = candidates.into_iter()
+        .filter(|w| seen.insert((w.text.clone(), w.reading.clone())))
+        .collect();
+    
+    if let Some(user) = options.user_dictionary {
+        user.override_words(&mut homophones);
+    }
+    
+    if options.same_part_of_speech {
+        retain_same_part_of_speech(&mut homophones, original_word, |w| (&w.text, w.jmdict_entry));
+    }
+    
+    if options.group_by_entry {
+        homophones = group_by_entry(homophones);
+    }
+    
+    if let Some(languages) = options.meanings {
+        for word in &mut homophones {
+            word.meanings = word.meanings(languages);
         }
     }
     
     // Sort by frequency score (higher is more common)
-    homophones.sort_by(|a, b| b.frequency_score.cmp(&a.frequency_score));
+    homophones.sort_by_key(|w| std::cmp::Reverse(w.frequency_score));
     
-    homophones
-}
\ No newline at end of file
+    (homophones, found_in_dictionary)
+}
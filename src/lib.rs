@@ -1,42 +1,371 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use names::{NameDictionary, NameEntry, NameType};
+use user_dict::{UserDictionary, UserEntry};
+use predict::PredictionRule;
+use affix::AffixAccent;
 
 mod nhk_data;
 pub mod kana_utils;
 mod katakana_support;
+pub mod pitch;
+pub mod names;
+pub mod user_dict;
+pub mod predict;
+pub mod affix;
+pub mod deinflect;
+pub mod conjugation_pitch;
+pub mod furigana;
+pub mod overlap;
+pub mod svg;
+mod groups;
+mod compound;
+mod confusability;
+mod suggest;
+mod gloss;
+mod error;
+mod filter;
+#[cfg(feature = "audio")]
+pub mod audio;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+mod python;
+
+pub use error::LookupError;
+pub use filter::{PartOfSpeech, WordFilter};
+pub use groups::{HomophoneGroup, MinimalPairFilter, MinimalPairSet, homophone_groups, pitch_minimal_pairs};
+pub use confusability::{Confusability, ConfusabilityOptions, ConfusableWord, pitch_overlap};
+pub use gloss::{GlossMatch, Meanings, entry_meanings, find_by_gloss, find_by_gloss_in};
+pub use suggest::{Suggestion, suggest};
+pub use compound::{CompoundAlternative, CompoundPart, compound_homophones, split_compound};
+pub use deinflect::{InflectedWord, InflectedWordWithPitch, find_inflected, find_inflected_with_nhk};
 
 
+/// How a result was reached from the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MatchKind {
+    /// The input is one of this word's kanji (or katakana) spellings
+    KanjiElement,
+    /// The input, as typed, is this word's reading
+    Reading,
+    /// The input converted from katakana to hiragana is this word's reading
+    KatakanaConversion,
+    /// Katakana input not in the dictionary, listed as itself with score 0
+    KatakanaSelfEntry,
+    /// This word shares a reading the input resolved to, e.g. 公正 for 構成 via こうせい
+    SharedReading,
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WordFrequency {
     pub text: String,
     pub reading: String,
     pub frequency_score: u32,
     pub is_common: bool,
+    pub match_kind: MatchKind,
+    pub source: WordSource,
+    /// JMDict sequence number, `None` for names, user words and katakana self entries
+    pub jmdict_entry: Option<u32>,
+    /// Other spellings of the same JMDict entry, filled by [`FindOptions::group_by_entry`]
+    pub variants: Vec<String>,
+    /// Meanings of the JMDict entry, filled by [`FindOptions::meanings`]
+    pub meanings: Option<Meanings>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WordFrequencyWithPitch {
     pub text: String,
     pub reading: String,
     pub frequency_score: u32,
     pub is_common: bool,
     pub pitch_accent: Vec<u8>,  // Multiple pitch accents in order of preference
+    pub pitch_source: PitchSource,
+    pub match_kind: MatchKind,
+    pub source: WordSource,
+    /// JMDict sequence number, `None` for names, user words and katakana self entries
+    pub jmdict_entry: Option<u32>,
+    /// Other spellings of the same JMDict entry, filled by [`FindOptions::group_by_entry`]
+    pub variants: Vec<String>,
+    /// Meanings of the JMDict entry, filled by [`FindOptions::meanings`]
+    pub meanings: Option<Meanings>,
+}
+
+/// Where a word's pitch accent comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PitchSource {
+    /// Attested in the NHK accent dictionary
+    Nhk,
+    /// Given in the user dictionary
+    User,
+    /// Estimated, see [`FindOptions::predict_pitch`]
+    Predicted { rule: PredictionRule },
+    /// No data, `pitch_accent` is empty
+    Unknown,
+}
+
+impl PitchSource {
+    fn nhk(pitches: &[u8]) -> PitchSource {
+        if pitches.is_empty() { PitchSource::Unknown } else { PitchSource::Nhk }
+    }
+}
+
+/// Dictionary a word comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum WordSource {
+    /// JMDict (also used for unknown katakana input listed as itself)
+    Jmdict,
+    /// A proper name, see [`FindOptions::names`]
+    Name { name_types: Vec<NameType> },
+    /// A user entry, or a JMDict word whose frequency or pitch it overrides,
+    /// see [`FindOptions::user_dictionary`]
+    User,
+}
+
+impl WordFrequency {
+    pub(crate) fn jmdict(entry: u32, text: &str, reading: &str, priority: &jmdict::Priority, match_kind: MatchKind) -> Self {
+        WordFrequency {
+            text: text.to_string(),
+            reading: reading.to_string(),
+            frequency_score: calculate_frequency_score(priority),
+            is_common: priority.is_common(),
+            match_kind,
+            source: WordSource::Jmdict,
+            jmdict_entry: Some(entry),
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
+
+    /// Names carry no frequency data, so they rank last
+    pub(crate) fn name(entry: &NameEntry, match_kind: MatchKind) -> Self {
+        WordFrequency {
+            text: entry.text.clone(),
+            reading: entry.reading.clone(),
+            frequency_score: 0,
+            is_common: false,
+            match_kind,
+            source: WordSource::Name { name_types: entry.name_types.clone() },
+            jmdict_entry: None,
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
+
+    pub(crate) fn user(entry: &UserEntry, match_kind: MatchKind) -> Self {
+        WordFrequency {
+            text: entry.text.clone(),
+            reading: entry.reading.clone(),
+            frequency_score: entry.frequency_score.unwrap_or(0),
+            is_common: false,
+            match_kind,
+            source: WordSource::User,
+            jmdict_entry: None,
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
+}
+
+impl WordFrequencyWithPitch {
+    pub(crate) fn jmdict(entry: u32, text: &str, reading: &str, priority: &jmdict::Priority, match_kind: MatchKind) -> Self {
+        let pitch_accent = nhk_data::get_pitch_accents(reading, text);
+        WordFrequencyWithPitch {
+            text: text.to_string(),
+            reading: reading.to_string(),
+            frequency_score: calculate_frequency_score(priority),
+            is_common: priority.is_common(),
+            pitch_source: PitchSource::nhk(&pitch_accent),
+            pitch_accent,
+            match_kind,
+            source: WordSource::Jmdict,
+            jmdict_entry: Some(entry),
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
+
+    /// Names carry no frequency data, so they rank last
+    pub(crate) fn name(entry: &NameEntry, match_kind: MatchKind) -> Self {
+        let pitch_accent = nhk_data::get_pitch_accents(&entry.reading, &entry.text);
+        WordFrequencyWithPitch {
+            text: entry.text.clone(),
+            reading: entry.reading.clone(),
+            frequency_score: 0,
+            is_common: false,
+            pitch_source: PitchSource::nhk(&pitch_accent),
+            pitch_accent,
+            match_kind,
+            source: WordSource::Name { name_types: entry.name_types.clone() },
+            jmdict_entry: None,
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
+
+    pub(crate) fn user(entry: &UserEntry, match_kind: MatchKind) -> Self {
+        let (pitch_accent, pitch_source) = match &entry.pitch_accent {
+            Some(pitches) => (pitches.clone(), PitchSource::User),
+            None => {
+                let pitches = nhk_data::get_pitch_accents(&entry.reading, &entry.text);
+                let source = PitchSource::nhk(&pitches);
+                (pitches, source)
+            }
+        };
+        WordFrequencyWithPitch {
+            text: entry.text.clone(),
+            reading: entry.reading.clone(),
+            frequency_score: entry.frequency_score.unwrap_or(0),
+            is_common: false,
+            pitch_accent,
+            pitch_source,
+            match_kind,
+            source: WordSource::User,
+            jmdict_entry: None,
+            variants: Vec::new(),
+            meanings: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(tag = "kind"))]
 pub enum FindWithNhkResult {
+    /// Nothing in the dictionary matched the input
+    NotFound,
     NoHomophones,
     UniqueMatch {
         true_homophones: Vec<WordFrequencyWithPitch>,
         different_pitch_homophones: Vec<WordFrequencyWithPitch>,
+        /// Readings (hiragana) the input resolved to
+        target_readings: Vec<String>,
+        /// Suffixes read like the input, with the accent they give compounds (〜あめ for 雨)
+        affixes: Vec<AffixAccent>,
     },
     MultipleMatches {
         homophones: Vec<WordFrequencyWithPitch>,
+        /// Readings (hiragana) the input resolved to
+        target_readings: Vec<String>,
+        /// Suffixes read like the input, with the accent they give compounds (〜あめ for あめ)
+        affixes: Vec<AffixAccent>,
     },
+    /// The input is a suffix such as 〜あめ, see [`affix::find_affix`]
+    Affix {
+        affixes: Vec<AffixAccent>,
+    },
+}
+
+/// Extra word sources for [`find_with_options`] and [`find_with_nhk_options`]
+///
+/// The default searches JMDict only, like [`find`] and [`find_with_nhk`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FindOptions<'a> {
+    /// Proper names to search alongside JMDict
+    pub names: Option<&'a NameDictionary>,
+    /// User entries to search alongside JMDict, overriding its frequency and pitch
+    pub user_dictionary: Option<&'a UserDictionary>,
+    /// Tags of JMDict words to leave out of the homophones
+    pub filter: Option<&'a WordFilter>,
+    /// List each JMDict entry once, with its other spellings in `variants`
+    /// (綺麗 with 奇麗), instead of once per spelling
+    pub group_by_entry: bool,
+    /// Estimate the accent of words missing from NHK data, marked
+    /// [`PitchSource::Predicted`], see [`predict::predict_accent`]
+    pub predict_pitch: bool,
+    /// Sort homophones by how likely they are to be mistaken for the input
+    /// instead of by frequency, see [`FindWithNhkResult::by_confusability`];
+    /// the input's own word stays first
+    pub confusability: Option<ConfusabilityOptions>,
+    /// Keep only homophones sharing a part of speech with the word the input
+    /// spells: 家庭 keeps 課程 and 仮定 but not a verb read かてい. No effect
+    /// when the input is a reading
+    pub same_part_of_speech: bool,
+    /// Fill each JMDict word's `meanings` in the first of these languages
+    /// its entry has, else English, see [`entry_meanings`]
+    pub meanings: Option<&'a [&'a str]>,
+}
+
+impl FindOptions<'_> {
+    /// Whether the filter keeps this reading of this entry
+    pub(crate) fn allows(&self, entry: &jmdict::Entry, reading: &jmdict::ReadingElement) -> bool {
+        self.filter.is_none_or(|f| f.allows_entry(entry) && f.allows_reading(reading))
+    }
+
+    pub(crate) fn allows_kanji(&self, kanji: &jmdict::KanjiElement) -> bool {
+        self.filter.is_none_or(|f| f.allows_kanji(kanji))
+    }
+
+    /// Spellings of `reading` listed as words, with their priority: the
+    /// reading itself in a kana-only entry, else each kanji element the
+    /// filter keeps
+    ///
+    /// The jmdict crate drops JMDict's `re_restr`, so every kanji element
+    /// is paired with every reading.
+    pub(crate) fn spellings(&self, entry: &jmdict::Entry, reading: &jmdict::ReadingElement) -> Vec<(&'static str, jmdict::Priority)> {
+        if entry.kanji_elements().count() == 0 {
+            vec![(reading.text, reading.priority)]
+        } else {
+            entry.kanji_elements()
+                .filter(|k| self.allows_kanji(k))
+                .map(|k| (k.text, k.priority))
+                .collect()
+        }
+    }
 }
 
+/// Homophones of a word, most frequent first
+///
+/// Input JMDict does not list, such as 買った or かいました, falls back to
+/// [`deinflect::find_inflected`]: the result is the inflected forms sharing
+/// its reading (買った, 飼った, 勝った, 刈った). Use `find_inflected` itself
+/// for the lemmas and inflection chains.
 pub fn find(word: &str) -> Vec<WordFrequency> {
+    let (homophones, found) = katakana_support::find_with_katakana_support(word, &FindOptions::default());
+    if !found {
+        let inflected = deinflect::inflected_words(word);
+        if !inflected.is_empty() {
+            return inflected;
+        }
+    }
+    homophones
+}
+
+/// [`find`] with extra word sources, see [`FindOptions`]; dictionary forms only
+pub fn find_with_options(word: &str, options: &FindOptions) -> Vec<WordFrequency> {
     // Use the enhanced function that handles katakana properly
-    katakana_support::find_with_katakana_support(word)
+    katakana_support::find_with_katakana_support(word, options).0
+}
+
+/// Like [`find`], but reports why nothing was found instead of returning an empty list
+///
+/// Unknown katakana input is an error here, rather than coming back as a
+/// single unranked entry for the input itself.
+pub fn try_find(word: &str) -> Result<Vec<WordFrequency>, LookupError> {
+    error::check_input(word)?;
+    match katakana_support::find_with_katakana_support(word, &FindOptions::default()) {
+        (homophones, true) => Ok(homophones),
+        (_, false) => match deinflect::inflected_words(word) {
+            inflected if inflected.is_empty() => Err(error::not_found(word)),
+            inflected => Ok(inflected),
+        },
+    }
+}
+
+/// Like [`find_with_nhk`], but reports why nothing was found instead of returning
+/// [`FindWithNhkResult::NotFound`]
+pub fn try_find_with_nhk(word: &str) -> Result<FindWithNhkResult, LookupError> {
+    error::check_input(word)?;
+    match find_with_nhk(word) {
+        FindWithNhkResult::NotFound => Err(error::not_found(word)),
+        result => Ok(result),
+    }
 }
 
 pub(crate) fn calculate_frequency_score(priority: &jmdict::Priority) -> u32 {
@@ -77,7 +406,24 @@ pub(crate) fn calculate_frequency_score(priority: &jmdict::Priority) -> u32 {
     score
 }
 
+/// Homophones of a word with their pitch accents
+///
+/// Inflected input falls back to [`deinflect::find_inflected_with_nhk`] as
+/// in [`find`], comparing the accents of the inflected forms.
 pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
+    match find_with_nhk_options(word, &FindOptions::default()) {
+        FindWithNhkResult::NotFound => deinflect::inflected_result(word),
+        result => result,
+    }
+}
+
+/// [`find_with_nhk`] with extra word sources, see [`FindOptions`]; dictionary forms only
+pub fn find_with_nhk_options(word: &str, options: &FindOptions) -> FindWithNhkResult {
+    if word.starts_with(['〜', '～']) {
+        let affixes: Vec<AffixAccent> = affix::find_affix(word).into_iter().cloned().collect();
+        return if affixes.is_empty() { FindWithNhkResult::NotFound } else { FindWithNhkResult::Affix { affixes } };
+    }
+
     // Convert katakana to hiragana if needed
     let search_word = if kana_utils::contains_katakana(word) {
         kana_utils::katakana_to_hiragana(word)
@@ -87,8 +433,7 @@ pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
     let original_word = word;
     let hiragana_word = search_word.as_str();
     
-    let mut homophones = Vec::new();
-    let mut reading_to_words: HashMap<String, Vec<(String, u32, bool, Vec<u8>)>> = HashMap::new();
+    let mut candidates: Vec<WordFrequencyWithPitch> = Vec::new();
     
     // First, find the target word's pitch accent and determine if input is unique
     let mut target_pitches: Vec<u8> = Vec::new();
@@ -117,12 +462,7 @@ pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
                         target_pitches = nhk_data::get_pitch_accents(reading.text, kanji.text);
                     }
                     
-                    let freq_score = calculate_frequency_score(&reading.priority);
-                    let pitches = nhk_data::get_pitch_accents(reading.text, kanji.text);
-                    let key = reading.text.to_string();
-                    reading_to_words.entry(key)
-                        .or_insert_with(Vec::new)
-                        .push((kanji.text.to_string(), freq_score, reading.priority.is_common(), pitches));
+                    candidates.push(WordFrequencyWithPitch::jmdict(entry.number, kanji.text, reading.text, &reading.priority, MatchKind::KanjiElement));
                 }
             }
         }
@@ -139,57 +479,116 @@ pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
                     target_pitches = nhk_data::get_pitch_accents(reading.text, reading.text);
                 }
                 
-                let freq_score = calculate_frequency_score(&reading.priority);
-                let key = reading.text.to_string();
+                let match_kind = if reading.text == original_word {
+                    MatchKind::Reading
+                } else {
+                    MatchKind::KatakanaConversion
+                };
                 
                 if entry.kanji_elements().count() == 0 {
+                    // The input spells this word exactly, so it is never filtered out
+                    if reading.text != original_word && !options.allows(&entry, &reading) {
+                        continue;
+                    }
                     // Check if this kana-only word is unique (no other words with same reading)
                     if reading.text == original_word && !found_exact_match {
                         found_exact_match = true;
                         exact_match_text = reading.text.to_string();
                     }
-                    let pitches = nhk_data::get_pitch_accents(reading.text, reading.text);
-                    reading_to_words.entry(key)
-                        .or_insert_with(Vec::new)
-                        .push((reading.text.to_string(), freq_score, reading.priority.is_common(), pitches));
-                } else {
-                    for kanji in entry.kanji_elements() {
-                        let kanji_freq_score = calculate_frequency_score(&kanji.priority);
-                        let pitches = nhk_data::get_pitch_accents(reading.text, kanji.text);
-                        reading_to_words.entry(key.clone())
-                            .or_insert_with(Vec::new)
-                            .push((kanji.text.to_string(), kanji_freq_score, kanji.priority.is_common(), pitches));
+                    candidates.push(WordFrequencyWithPitch::jmdict(entry.number, reading.text, reading.text, &reading.priority, match_kind));
+                } else if options.allows(&entry, &reading) {
+                    for kanji in entry.kanji_elements().filter(|k| options.allows_kanji(k)) {
+                        candidates.push(WordFrequencyWithPitch::jmdict(entry.number, kanji.text, reading.text, &kanji.priority, match_kind));
                     }
                 }
             }
         }
     }
     
+    // Names spelled like the input; their readings join the search
+    if let Some(names) = options.names {
+        for name in names.with_text(original_word) {
+            if !found_exact_match {
+                found_exact_match = true;
+                exact_match_text = name.text.clone();
+            }
+            if !target_readings.contains(&name.reading) {
+                target_readings.push(name.reading.clone());
+            }
+            if target_pitches.is_empty() {
+                target_pitches = nhk_data::get_pitch_accents(&name.reading, &name.text);
+            }
+            let match_kind = if name.text == name.reading {
+                MatchKind::Reading
+            } else {
+                MatchKind::KanjiElement
+            };
+            candidates.push(WordFrequencyWithPitch::name(name, match_kind));
+        }
+    }
+    
+    // User entries spelled like the input; their pitch wins over NHK data
+    if let Some(user) = options.user_dictionary {
+        for entry in user.with_text(original_word) {
+            if !found_exact_match {
+                found_exact_match = true;
+                exact_match_text = entry.text.clone();
+            }
+            if !target_readings.contains(&entry.reading) {
+                target_readings.push(entry.reading.clone());
+            }
+            if let Some(pitches) = &entry.pitch_accent {
+                target_pitches = pitches.clone();
+            } else if target_pitches.is_empty() {
+                target_pitches = nhk_data::get_pitch_accents(&entry.reading, &entry.text);
+            }
+            let match_kind = if entry.text == entry.reading {
+                MatchKind::Reading
+            } else {
+                MatchKind::KanjiElement
+            };
+            candidates.push(WordFrequencyWithPitch::user(entry, match_kind));
+        }
+    }
+    
+    // Readings are compared in hiragana: names often have katakana readings
+    let normalized_targets: Vec<String> = target_readings.iter()
+        .map(|r| kana_utils::katakana_to_hiragana(r))
+        .collect();
+    let is_target_reading = |reading: &str| normalized_targets.contains(&kana_utils::katakana_to_hiragana(reading));
+    
     // Second pass: collect all words with the same readings
     if !target_readings.is_empty() {
         for entry in jmdict::entries() {
             for reading in entry.reading_elements() {
-                if target_readings.contains(&reading.text.to_string()) {
-                    let freq_score = calculate_frequency_score(&reading.priority);
-                    let key = reading.text.to_string();
-                    
-                    if entry.kanji_elements().count() == 0 {
-                        let pitches = nhk_data::get_pitch_accents(reading.text, reading.text);
-                        reading_to_words.entry(key)
-                            .or_insert_with(Vec::new)
-                            .push((reading.text.to_string(), freq_score, reading.priority.is_common(), pitches));
-                    } else {
-                        for kanji in entry.kanji_elements() {
-                            let kanji_freq_score = calculate_frequency_score(&kanji.priority);
-                            let pitches = nhk_data::get_pitch_accents(reading.text, kanji.text);
-                            reading_to_words.entry(key.clone())
-                                .or_insert_with(Vec::new)
-                                .push((kanji.text.to_string(), kanji_freq_score, kanji.priority.is_common(), pitches));
-                        }
+                if is_target_reading(reading.text) && options.allows(&entry, &reading) {
+                    for (text, priority) in options.spellings(&entry, &reading) {
+                        candidates.push(WordFrequencyWithPitch::jmdict(entry.number, text, reading.text, &priority, MatchKind::SharedReading));
                     }
                 }
             }
         }
+        
+        if let Some(names) = options.names {
+            for reading in &normalized_targets {
+                for name in names.with_reading(reading) {
+                    candidates.push(WordFrequencyWithPitch::name(name, reading_match_kind(&name.reading, original_word, hiragana_word)));
+                }
+            }
+        }
+        
+        if let Some(user) = options.user_dictionary {
+            for reading in &target_readings {
+                for entry in user.with_reading(reading) {
+                    candidates.push(WordFrequencyWithPitch::user(entry, reading_match_kind(&entry.reading, original_word, hiragana_word)));
+                }
+            }
+        }
+    }
+    
+    // Everything collected so far came from the dictionary
+    if candidates.is_empty() {
+        return FindWithNhkResult::NotFound;
     }
     
     // If input was katakana, also include katakana entries
@@ -199,63 +598,83 @@ pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
             for kanji in entry.kanji_elements() {
                 if kanji.text == original_word {
                     // Found the katakana entry
-                    let freq_score = calculate_frequency_score(&kanji.priority);
-                    let pitches = nhk_data::get_pitch_accents(hiragana_word, original_word);
-                    reading_to_words.entry(hiragana_word.to_string())
-                        .or_insert_with(Vec::new)
-                        .push((original_word.to_string(), freq_score, kanji.priority.is_common(), pitches));
+                    candidates.push(WordFrequencyWithPitch::jmdict(entry.number, original_word, hiragana_word, &kanji.priority, MatchKind::KanjiElement));
                     break;
                 }
             }
         }
         
         // Also add the katakana itself even if not in JMDict
-        let has_katakana_entry = reading_to_words.get(hiragana_word)
-            .map(|words| words.iter().any(|(text, _, _, _)| text == original_word))
-            .unwrap_or(false);
+        let has_katakana_entry = candidates.iter()
+            .any(|w| w.text == original_word && w.reading == hiragana_word);
             
         if !has_katakana_entry {
-            reading_to_words.entry(hiragana_word.to_string())
-                .or_insert_with(Vec::new)
-                .push((original_word.to_string(), 0, false, vec![]));
+            candidates.push(WordFrequencyWithPitch {
+                text: original_word.to_string(),
+                reading: hiragana_word.to_string(),
+                frequency_score: 0,
+                is_common: false,
+                pitch_accent: vec![],
+                pitch_source: PitchSource::Unknown,
+                match_kind: MatchKind::KatakanaSelfEntry,
+                source: WordSource::Jmdict,
+                jmdict_entry: None,
+                variants: Vec::new(),
+                meanings: None,
+            });
         }
     }
     
-    // Convert to output format and deduplicate
+    // Deduplicate, keeping the first (most direct) match of each word
     let mut seen = std::collections::HashSet::new();
-    for (reading, words) in reading_to_words {
-        for (text, freq_score, is_common, pitches) in words {
-            let key = (text.clone(), reading.clone());
-            if seen.insert(key) {
-                homophones.push(WordFrequencyWithPitch {
-                    text,
-                    reading: reading.clone(),
-                    frequency_score: freq_score,
-                    is_common,
-                    pitch_accent: pitches,
-                });
-            }
+    let mut homophones: Vec<WordFrequencyWithPitch> = candidates.into_iter()
+        .filter(|w| seen.insert((w.text.clone(), w.reading.clone())))
+        .collect();
+    
+    if let Some(user) = options.user_dictionary {
+        user.override_pitched_words(&mut homophones);
+    }
+    
+    if options.same_part_of_speech {
+        filter::retain_same_part_of_speech(&mut homophones, original_word, |w| (&w.text, w.jmdict_entry));
+    }
+    
+    if options.group_by_entry {
+        homophones = group_by_entry(homophones);
+    }
+    
+    if let Some(languages) = options.meanings {
+        for word in &mut homophones {
+            word.meanings = word.meanings(languages);
+        }
+    }
+    
+    if options.predict_pitch {
+        predict::fill_missing_pitches(&mut homophones);
+        if target_pitches.is_empty()
+            && let Some(target) = homophones.iter()
+                .find(|w| w.text == exact_match_text && is_target_reading(&w.reading))
+        {
+            target_pitches = target.pitch_accent.clone();
         }
     }
     
     // Sort by frequency score (higher is more common)
-    homophones.sort_by(|a, b| b.frequency_score.cmp(&a.frequency_score));
+    homophones.sort_by_key(|w| std::cmp::Reverse(w.frequency_score));
     
     // Determine result type based on the input and results
     if found_exact_match {
-        // Get all unique word texts (not counting different readings of same word)
-        let unique_texts: std::collections::HashSet<String> = homophones.iter()
-            .filter(|w| target_readings.contains(&w.reading))
-            .map(|w| w.text.clone())
-            .collect();
+        // Count distinct words (not counting other spellings or readings of the same entry)
+        let distinct_words = distinct_word_count(homophones.iter()
+            .filter(|w| is_target_reading(&w.reading)));
         
-        // If only one unique word text exists, it has no homophones
-        if unique_texts.len() == 1 {
+        // If only one word exists, it has no homophones
+        if distinct_words == 1 {
             FindWithNhkResult::NoHomophones
         } else {
             // Filter to only homophones with matching readings
             let same_reading_words: Vec<_> = homophones.into_iter()
-                .filter(|w| target_readings.contains(&w.reading))
+                .filter(|w| is_target_reading(&w.reading))
                 .collect();
             
             // Input matches a specific word - divide into true/fake homophones
@@ -279,28 +698,131 @@ pub fn find_with_nhk(word: &str) -> FindWithNhkResult {
                 }
             }
             
+            if let Some(confusability) = &options.confusability {
+                let target = confusability::input_word(&true_homophones).cloned();
+                confusability::sort_by_confusability(&mut true_homophones, target.as_ref(), confusability);
+                confusability::sort_by_confusability(&mut different_pitch_homophones, target.as_ref(), confusability);
+                let (mut input, rest): (Vec<_>, Vec<_>) = true_homophones.into_iter()
+                    .partition(|w| w.match_kind != MatchKind::SharedReading);
+                input.extend(rest);
+                true_homophones = input;
+            }
+
+            let affixes = affix::read_as(&normalized_targets);
             FindWithNhkResult::UniqueMatch {
                 true_homophones,
                 different_pitch_homophones,
+                target_readings,
+                affixes,
             }
         }
     } else {
         // Input is a reading (like hiragana) - check if it's specific enough
-        // Get all unique word texts
-        let unique_texts: std::collections::HashSet<String> = homophones.iter()
-            .map(|w| w.text.clone())
-            .collect();
-        
-        if unique_texts.len() == 1 {
+        // Count distinct words
+        if distinct_word_count(homophones.iter()) == 1 {
             // Only one unique word (might have multiple readings)
             FindWithNhkResult::NoHomophones
         } else {
             // Multiple different words - return them as MultipleMatches
-            FindWithNhkResult::MultipleMatches { homophones }
+            let mut homophones = homophones;
+            if let Some(confusability) = &options.confusability {
+                confusability::sort_by_confusability(&mut homophones, None, confusability);
+            }
+            let affixes = affix::read_as(&normalized_targets);
+            FindWithNhkResult::MultipleMatches { homophones, target_readings, affixes }
+        }
+    }
+}
+
+/// A result word that other spellings of its JMDict entry can be folded into
+pub(crate) trait EntryVariants {
+    /// JMDict entry and reading, `None` if not from JMDict
+    fn entry_key(&self) -> Option<(u32, String)>;
+    fn absorb(&mut self, variant: Self);
+}
+
+impl EntryVariants for WordFrequency {
+    fn entry_key(&self) -> Option<(u32, String)> {
+        self.jmdict_entry.map(|entry| (entry, self.reading.clone()))
+    }
+
+    fn absorb(&mut self, variant: Self) {
+        self.frequency_score = self.frequency_score.max(variant.frequency_score);
+        self.is_common |= variant.is_common;
+        if variant.text != self.text && !self.variants.contains(&variant.text) {
+            self.variants.push(variant.text);
         }
     }
 }
 
+impl EntryVariants for WordFrequencyWithPitch {
+    fn entry_key(&self) -> Option<(u32, String)> {
+        self.jmdict_entry.map(|entry| (entry, self.reading.clone()))
+    }
+
+    fn absorb(&mut self, variant: Self) {
+        self.frequency_score = self.frequency_score.max(variant.frequency_score);
+        self.is_common |= variant.is_common;
+        if self.pitch_accent.is_empty() {
+            self.pitch_accent = variant.pitch_accent;
+            self.pitch_source = variant.pitch_source;
+        }
+        if variant.text != self.text && !self.variants.contains(&variant.text) {
+            self.variants.push(variant.text);
+        }
+    }
+}
+
+/// Fold every spelling of one entry and reading into the first one listed
+///
+/// Candidates list the spelling the input matched first, then JMDict order,
+/// so the primary spelling is the input's or the entry's preferred one.
+pub(crate) fn group_by_entry<W: EntryVariants>(words: Vec<W>) -> Vec<W> {
+    let mut grouped: Vec<W> = Vec::new();
+    let mut index: HashMap<(u32, String), usize> = HashMap::new();
+    for word in words {
+        match word.entry_key() {
+            Some(key) => match index.get(&key) {
+                Some(&i) => grouped[i].absorb(word),
+                None => {
+                    index.insert(key, grouped.len());
+                    grouped.push(word);
+                }
+            },
+            None => grouped.push(word),
+        }
+    }
+    grouped
+}
+
+/// Number of distinct words, counting all spellings of a JMDict entry once
+///
+/// Words outside JMDict count by text, unless a JMDict word has that text
+/// (katakana input listed alongside its own entry).
+pub(crate) fn distinct_word_count<'a>(words: impl Iterator<Item = &'a WordFrequencyWithPitch> + Clone) -> usize {
+    let entries: HashSet<u32> = words.clone().filter_map(|w| w.jmdict_entry).collect();
+    let entry_texts: HashSet<&str> = words.clone()
+        .filter(|w| w.jmdict_entry.is_some())
+        .flat_map(|w| std::iter::once(w.text.as_str()).chain(w.variants.iter().map(String::as_str)))
+        .collect();
+    let other_texts: HashSet<&str> = words
+        .filter(|w| w.jmdict_entry.is_none() && !entry_texts.contains(w.text.as_str()))
+        .map(|w| w.text.as_str())
+        .collect();
+    entries.len() + other_texts.len()
+}
+
+/// How a name or user entry found by one of the target readings relates to the input
+pub(crate) fn reading_match_kind(reading: &str, original_word: &str, hiragana_word: &str) -> MatchKind {
+    if reading == original_word {
+        MatchKind::Reading
+    } else if reading == hiragana_word {
+        MatchKind::KatakanaConversion
+    } else {
+        MatchKind::SharedReading
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -364,7 +886,7 @@ mod tests {
         // Test searching by reading - should return MultipleMatches
         let result = find_with_nhk("こうせい");
         match result {
-            FindWithNhkResult::MultipleMatches { homophones } => {
+            FindWithNhkResult::MultipleMatches { homophones, .. } => {
                 assert!(!homophones.is_empty());
                 
                 // Find entries with pitch data
@@ -380,7 +902,7 @@ mod tests {
         // Test searching by specific word - should return UniqueMatch
         let result2 = find_with_nhk("構成");
         match result2 {
-            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones } => {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
                 // Find 構成 in true homophones
                 let kousei = true_homophones.iter().find(|w| w.text == "構成");
                 assert!(kousei.is_some(), "構成 should be in true_homophones");
@@ -389,17 +911,16 @@ mod tests {
                 let kousei2 = true_homophones.iter().find(|w| w.text == "後世")
                     .or_else(|| different_pitch_homophones.iter().find(|w| w.text == "後世"));
                 
-                if let (Some(k1), Some(k2)) = (kousei, kousei2) {
-                    if !k1.pitch_accent.is_empty() && !k2.pitch_accent.is_empty() {
-                        // Check if they have common pitch
-                        let have_common_pitch = k1.pitch_accent.iter()
-                            .any(|p| k2.pitch_accent.contains(p));
-                        
-                        if have_common_pitch {
-                            assert!(true_homophones.iter().any(|w| w.text == "後世"));
-                        } else {
-                            assert!(different_pitch_homophones.iter().any(|w| w.text == "後世"));
-                        }
+                if let (Some(k1), Some(k2)) = (kousei, kousei2)
+                    && !k1.pitch_accent.is_empty() && !k2.pitch_accent.is_empty() {
+                    // Check if they have common pitch
+                    let have_common_pitch = k1.pitch_accent.iter()
+                        .any(|p| k2.pitch_accent.contains(p));
+                    
+                    if have_common_pitch {
+                        assert!(true_homophones.iter().any(|w| w.text == "後世"));
+                    } else {
+                        assert!(different_pitch_homophones.iter().any(|w| w.text == "後世"));
                     }
                 }
             }
@@ -432,7 +953,7 @@ mod tests {
         // When searching by reading, should return MultipleMatches
         let result = find_with_nhk("はし");
         match result {
-            FindWithNhkResult::MultipleMatches { homophones } => {
+            FindWithNhkResult::MultipleMatches { homophones, .. } => {
                 assert!(!homophones.is_empty());
                 // All words should be present
                 assert!(homophones.iter().any(|w| w.text == "橋"));
@@ -444,7 +965,7 @@ mod tests {
         // Test searching by specific word
         let result_bridge = find_with_nhk("橋");
         match result_bridge {
-            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones } => {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
                 let bridge = true_homophones.iter().find(|w| w.text == "橋");
                 assert!(bridge.is_some(), "橋 should be in true_homophones");
                 
@@ -452,17 +973,16 @@ mod tests {
                 let chopsticks = true_homophones.iter().find(|w| w.text == "箸")
                     .or_else(|| different_pitch_homophones.iter().find(|w| w.text == "箸"));
                 
-                if let (Some(b), Some(c)) = (bridge, chopsticks) {
-                    if !b.pitch_accent.is_empty() && !c.pitch_accent.is_empty() {
-                        // They should have different pitch accents
-                        let have_common_pitch = b.pitch_accent.iter()
-                            .any(|p| c.pitch_accent.contains(p));
-                        
-                        if have_common_pitch {
-                            assert!(true_homophones.iter().any(|w| w.text == "箸"));
-                        } else {
-                            assert!(different_pitch_homophones.iter().any(|w| w.text == "箸"));
-                        }
+                if let (Some(b), Some(c)) = (bridge, chopsticks)
+                    && !b.pitch_accent.is_empty() && !c.pitch_accent.is_empty() {
+                    // They should have different pitch accents
+                    let have_common_pitch = b.pitch_accent.iter()
+                        .any(|p| c.pitch_accent.contains(p));
+                    
+                    if have_common_pitch {
+                        assert!(true_homophones.iter().any(|w| w.text == "箸"));
+                    } else {
+                        assert!(different_pitch_homophones.iter().any(|w| w.text == "箸"));
                     }
                 }
             }
@@ -491,10 +1011,11 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(not(feature = "uncommon"), ignore = "the other ていど, 低度, is an uncommon entry")]
     fn test_multiple_pitch_accents() {
         let result = find_with_nhk("ていど");
         match result {
-            FindWithNhkResult::MultipleMatches { homophones } => {
+            FindWithNhkResult::MultipleMatches { homophones, .. } => {
                 // Find 程度
                 let teido = homophones.iter().find(|w| w.text == "程度");
                 assert!(teido.is_some());
@@ -538,4 +1059,329 @@ mod tests {
             _ => panic!("Expected NoHomophones for にほんご"),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_not_found() {
+        // 中国語 is known but has no homophones; garbage matches nothing at all
+        assert_eq!(find_with_nhk("中国語"), FindWithNhkResult::NoHomophones);
+        assert_eq!(find_with_nhk("hello"), FindWithNhkResult::NotFound);
+        assert_eq!(find_with_nhk(""), FindWithNhkResult::NotFound);
+        assert_eq!(find_with_nhk("ズヴォヅ"), FindWithNhkResult::NotFound);
+    }
+
+    #[test]
+    fn test_lookup_errors() {
+        assert_eq!(try_find(""), Err(LookupError::EmptyInput));
+        assert_eq!(try_find("  "), Err(LookupError::EmptyInput));
+        assert_eq!(try_find("hello"), Err(LookupError::UnsupportedScript));
+        assert_eq!(try_find("hello構成"), Err(LookupError::MixedScript));
+        assert_eq!(
+            try_find("ズヴォヅ"),
+            Err(LookupError::NotInDictionary { word: "ズヴォヅ".to_string() })
+        );
+        assert_eq!(try_find_with_nhk("ぬぬぬぬ"), Err(LookupError::NotInDictionary { word: "ぬぬぬぬ".to_string() }));
+
+        assert!(try_find("かう").unwrap().iter().any(|w| w.text == "買う"));
+        assert_eq!(try_find_with_nhk("中国語"), Ok(FindWithNhkResult::NoHomophones));
+        assert!(matches!(try_find_with_nhk("構成"), Ok(FindWithNhkResult::UniqueMatch { .. })));
+        assert_eq!(try_find_with_nhk("〜"), Err(LookupError::EmptyInput));
+        assert_eq!(try_find_with_nhk("〜ぬぬぬぬ"), Err(LookupError::NotInDictionary { word: "〜ぬぬぬぬ".to_string() }));
+    }
+
+    #[test]
+    fn test_inflected_fallback() {
+        let katta = find("買った");
+        for word in ["買った", "飼った", "勝った", "刈った"] {
+            assert!(katta.iter().any(|w| w.text == word), "{word}");
+        }
+        assert!(find("かいました").iter().any(|w| w.text == "買いました" && w.reading == "かいました"));
+        assert!(try_find("買った").is_ok());
+        assert!(find_with_options("買った", &FindOptions::default()).is_empty());
+
+        // 買った[0] and 飼った[1] sound different
+        let FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, target_readings, .. } = find_with_nhk("買った") else {
+            panic!("買った should be a unique match");
+        };
+        assert_eq!(target_readings, vec!["かった".to_string()]);
+        assert!(true_homophones.iter().any(|w| w.text == "買った" && w.pitch_accent == vec![0]));
+        assert!(different_pitch_homophones.iter().any(|w| w.text == "飼った"));
+        assert!(matches!(find_with_nhk("かった"), FindWithNhkResult::MultipleMatches { .. }));
+        assert_eq!(find_with_nhk("ぬぬった"), FindWithNhkResult::NotFound);
+    }
+
+    #[test]
+    fn test_affix_lookup() {
+        for query in ["〜あめ", "～雨"] {
+            let Ok(FindWithNhkResult::Affix { affixes }) = try_find_with_nhk(query) else {
+                panic!("{query} should be an affix query");
+            };
+            assert!(affixes.iter().any(|a| a.text == "雨" && a.rule == affix::AffixAccentRule::SuffixFirstMora));
+        }
+
+        let FindWithNhkResult::MultipleMatches { affixes, .. } = find_with_nhk("あめ") else {
+            panic!("あめ should match several words");
+        };
+        assert!(affixes.iter().any(|a| a.text == "雨"));
+
+        let FindWithNhkResult::UniqueMatch { affixes, .. } = find_with_nhk("構成") else {
+            panic!("構成 should be a unique match");
+        };
+        assert!(affixes.is_empty());
+    }
+
+    #[test]
+    fn test_match_kind() {
+        let kind_of = |results: &[WordFrequencyWithPitch], text: &str| {
+            results.iter().find(|w| w.text == text).unwrap().match_kind
+        };
+
+        match find_with_nhk("構成") {
+            FindWithNhkResult::UniqueMatch { true_homophones, target_readings, .. } => {
+                assert_eq!(target_readings, vec!["こうせい".to_string()]);
+                assert_eq!(kind_of(&true_homophones, "構成"), MatchKind::KanjiElement);
+                assert_eq!(kind_of(&true_homophones, "公正"), MatchKind::SharedReading);
+            }
+            _ => panic!("Expected UniqueMatch for 構成"),
+        }
+
+        match find_with_nhk("こうせい") {
+            FindWithNhkResult::MultipleMatches { homophones, target_readings, .. } => {
+                assert_eq!(target_readings, vec!["こうせい".to_string()]);
+                assert!(homophones.iter().all(|w| w.match_kind == MatchKind::Reading));
+            }
+            _ => panic!("Expected MultipleMatches for こうせい"),
+        }
+
+        let results = find("コウセイ");
+        let kousei = results.iter().find(|w| w.text == "構成").unwrap();
+        assert_eq!(kousei.match_kind, MatchKind::KatakanaConversion);
+        let self_entry = results.iter().find(|w| w.text == "コウセイ").unwrap();
+        assert_eq!(self_entry.match_kind, MatchKind::KatakanaSelfEntry);
+        assert_eq!(self_entry.frequency_score, 0);
+    }
+
+    #[test]
+    fn test_names() {
+        let mut names = NameDictionary::new();
+        names.insert(NameEntry {
+            text: "佐藤".to_string(),
+            reading: "さとう".to_string(),
+            name_types: vec![NameType::Surname],
+        });
+        let options = FindOptions { names: Some(&names), ..Default::default() };
+
+        // Names are off unless passed in
+        assert_eq!(find_with_nhk("佐藤"), FindWithNhkResult::NotFound);
+        assert!(!find("さとう").iter().any(|w| w.text == "佐藤"));
+
+        match find_with_nhk_options("佐藤", &options) {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, target_readings, .. } => {
+                assert_eq!(target_readings, vec!["さとう".to_string()]);
+                let all: Vec<_> = true_homophones.iter().chain(&different_pitch_homophones).collect();
+                let satou = all.iter().find(|w| w.text == "佐藤").unwrap();
+                assert_eq!(satou.match_kind, MatchKind::KanjiElement);
+                assert_eq!(satou.source, WordSource::Name { name_types: vec![NameType::Surname] });
+                let sugar = all.iter().find(|w| w.text == "砂糖").unwrap();
+                assert_eq!(sugar.source, WordSource::Jmdict);
+            }
+            other => panic!("Expected UniqueMatch for 佐藤, got {:?}", other),
+        }
+
+        let results = find_with_options("サトウ", &options);
+        let satou = results.iter().find(|w| w.text == "佐藤").unwrap();
+        assert_eq!(satou.match_kind, MatchKind::KatakanaConversion);
+        // Names have no frequency data and rank after dictionary words
+        assert_eq!(results.last().unwrap().frequency_score, 0);
+
+        // Names read in katakana are homophones of the hiragana reading
+        names.insert(NameEntry {
+            text: "サトー".to_string(),
+            reading: "サトウ".to_string(),
+            name_types: vec![NameType::Company],
+        });
+        let options = FindOptions { names: Some(&names), ..Default::default() };
+        match find_with_nhk_options("佐藤", &options) {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
+                assert!(true_homophones.iter().chain(&different_pitch_homophones).any(|w| w.text == "サトー"));
+            }
+            other => panic!("Expected UniqueMatch for 佐藤, got {:?}", other),
+        }
+        match find_with_nhk_options("さとう", &options) {
+            FindWithNhkResult::MultipleMatches { homophones, .. } => {
+                assert!(homophones.iter().any(|w| w.text == "サトー"));
+            }
+            other => panic!("Expected MultipleMatches for さとう, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_dictionary() {
+        let mut user = UserDictionary::new();
+        user.insert(UserEntry::new("寛解", "かんかい").with_pitch_accent(vec![0]).with_frequency_score(30000));
+        user.insert(UserEntry::new("高声", "こうせい").with_pitch_accent(vec![0]));
+        // Overrides the JMDict word
+        user.insert(UserEntry::new("校正", "こうせい").with_pitch_accent(vec![0]).with_frequency_score(99999));
+        let options = FindOptions { user_dictionary: Some(&user), ..Default::default() };
+
+        assert!(find("寛解").is_empty());
+        let results = find_with_options("寛解", &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].frequency_score, 30000);
+        assert_eq!(results[0].source, WordSource::User);
+
+        let results = find_with_options("こうせい", &options);
+        assert_eq!(results[0].text, "校正");
+        assert_eq!(results[0].source, WordSource::User);
+        let kousei = results.iter().find(|w| w.text == "構成").unwrap();
+        assert_eq!(kousei.source, WordSource::Jmdict);
+        let user_word = results.iter().find(|w| w.text == "高声").unwrap();
+        assert_eq!(user_word.match_kind, MatchKind::Reading);
+
+        match find_with_nhk_options("構成", &options) {
+            FindWithNhkResult::UniqueMatch { true_homophones, .. } => {
+                // Both overridden to 構成's pitch 0
+                let kousei = true_homophones.iter().find(|w| w.text == "校正").unwrap();
+                assert_eq!(kousei.pitch_accent, vec![0]);
+                assert_eq!(kousei.frequency_score, 99999);
+                assert!(true_homophones.iter().any(|w| w.text == "高声"));
+            }
+            other => panic!("Expected UniqueMatch for 構成, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = WordFilter {
+            exclude_kanji_info: vec!["rK".to_string()],
+            exclude_sense_info: vec!["arch".to_string(), "vulg".to_string()],
+            exclude_fields: vec!["law".to_string()],
+            ..Default::default()
+        };
+        let options = FindOptions { filter: Some(&filter), ..Default::default() };
+
+        let texts = |results: Vec<WordFrequency>| results.into_iter().map(|w| w.text).collect::<Vec<_>>();
+
+        let kau = texts(find_with_options("かう", &options));
+        assert!(kau.contains(&"買う".to_string()));
+        assert!(!kau.contains(&"支う".to_string()));
+
+        let kaki = texts(find_with_options("かき", &options));
+        assert!(kaki.contains(&"牡蠣".to_string()));
+        assert!(!kaki.contains(&"牡蛎".to_string()));
+
+        // The word the input spells is kept (牡蛎 is a rare spelling)
+        if cfg!(all(feature = "uncommon", feature = "archaic")) {
+            assert!(texts(find_with_options("牡蛎", &options)).contains(&"牡蛎".to_string()));
+        }
+
+        match find_with_nhk_options("構成", &options) {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
+                assert!(!true_homophones.iter().chain(&different_pitch_homophones).any(|w| w.text == "更正"));
+            }
+            other => panic!("Expected UniqueMatch for 構成, got {:?}", other),
+        }
+
+        let medical = WordFilter { only_fields: vec!["med".to_string()], ..Default::default() };
+        let options = FindOptions { filter: Some(&medical), ..Default::default() };
+        assert_eq!(texts(find_with_options("みる", &options)), vec!["診る".to_string()]);
+    }
+
+    #[test]
+    fn test_part_of_speech() {
+        let texts = |results: Vec<WordFrequency>| results.into_iter().map(|w| w.text).collect::<Vec<_>>();
+
+        // 菊 is a noun, 聞く and 効く verbs
+        let options = FindOptions { same_part_of_speech: true, ..Default::default() };
+        let kiku = texts(find_with_options("聞く", &options));
+        assert!(kiku.contains(&"効く".to_string()));
+        assert!(!kiku.contains(&"菊".to_string()));
+        match find_with_nhk_options("菊", &options) {
+            FindWithNhkResult::NoHomophones => {}
+            other => panic!("Expected NoHomophones for 菊, got {:?}", other),
+        }
+        // A reading has no part of speech of its own
+        assert!(texts(find_with_options("きく", &options)).contains(&"菊".to_string()));
+
+        let filter = WordFilter { only_categories: vec![PartOfSpeech::Noun], ..Default::default() };
+        let options = FindOptions { filter: Some(&filter), ..Default::default() };
+        assert_eq!(texts(find_with_options("きく", &options)), vec!["菊".to_string()]);
+    }
+
+    #[test]
+    fn test_group_by_entry() {
+        // 綺麗 and 奇麗 are spellings of one entry, not homophones
+        assert_eq!(find_with_nhk("綺麗"), FindWithNhkResult::NoHomophones);
+
+        let ungrouped = find("かける");
+        assert!(ungrouped.iter().any(|w| w.text == "懸ける"));
+
+        let options = FindOptions { group_by_entry: true, ..Default::default() };
+        let grouped = find_with_options("かける", &options);
+        assert_eq!(grouped.len(), ungrouped.len() - 1);
+        let kakeru = grouped.iter().find(|w| w.text == "掛ける").unwrap();
+        assert_eq!(kakeru.variants, vec!["懸ける".to_string()]);
+        assert!(!grouped.iter().any(|w| w.text == "懸ける"));
+
+        // The spelling the input matched stays primary
+        match find_with_nhk_options("懸ける", &options) {
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } => {
+                let all: Vec<_> = true_homophones.iter().chain(&different_pitch_homophones).collect();
+                let kakeru = all.iter().find(|w| w.text == "懸ける").unwrap();
+                assert_eq!(kakeru.variants, vec!["掛ける".to_string()]);
+                assert_eq!(kakeru.match_kind, MatchKind::KanjiElement);
+                assert!(!all.iter().any(|w| w.text == "掛ける"));
+            }
+            other => panic!("Expected UniqueMatch for 懸ける, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_find_meanings() {
+        assert!(find("はし").iter().all(|w| w.meanings.is_none()));
+
+        let options = FindOptions { meanings: Some(&["ger"]), ..Default::default() };
+        let words = find_with_options("はし", &options);
+        assert!(words.iter().all(|w| w.meanings.as_ref().is_some_and(|m| !m.senses.is_empty())));
+        let bridge = words.iter().find(|w| w.text == "橋").unwrap().meanings.as_ref().unwrap();
+        if cfg!(feature = "all-glosses") {
+            assert_eq!(bridge.language.as_deref(), Some("ger"));
+            assert!(bridge.senses.iter().flatten().any(|g| g == "Brücke"));
+        }
+
+        // A code JMDict has no glosses in falls back to English
+        let options = FindOptions { meanings: Some(&["xxx"]), ..Default::default() };
+        let words = find_with_options("はし", &options);
+        let chopsticks = words.iter().find(|w| w.text == "箸").unwrap().meanings.as_ref().unwrap();
+        assert_eq!(chopsticks.language.as_deref(), Some("eng"));
+        assert_eq!(chopsticks.missing, vec!["xxx".to_string()]);
+
+        let FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, .. } = find_with_nhk_options("構成", &options) else {
+            panic!("構成 should be a unique match");
+        };
+        assert!(true_homophones.iter().chain(&different_pitch_homophones).all(|w| w.meanings.is_some()));
+    }
+
+    #[test]
+    #[cfg_attr(not(all(feature = "uncommon", feature = "archaic")), ignore = "支う is an uncommon, archaic entry")]
+    fn test_predict_pitch() {
+        let homophones = |result| match result {
+            FindWithNhkResult::MultipleMatches { homophones, .. } => homophones,
+            other => panic!("Expected MultipleMatches for かう, got {:?}", other),
+        };
+
+        let attested = homophones(find_with_nhk("かう"));
+        let kau = attested.iter().find(|w| w.text == "買う").unwrap();
+        assert_eq!(kau.pitch_source, PitchSource::Nhk);
+        let sasau = attested.iter().find(|w| w.text == "支う").unwrap();
+        assert!(sasau.pitch_accent.is_empty());
+        assert_eq!(sasau.pitch_source, PitchSource::Unknown);
+
+        let options = FindOptions { predict_pitch: true, ..Default::default() };
+        let predicted = homophones(find_with_nhk_options("かう", &options));
+        assert!(predicted.iter().all(|w| !w.pitch_accent.is_empty()));
+        let sasau = predicted.iter().find(|w| w.text == "支う").unwrap();
+        assert_eq!(sasau.pitch_source, PitchSource::Predicted { rule: PredictionRule::Verb });
+        // NHK data is kept where it exists
+        assert_eq!(predicted.iter().find(|w| w.text == "買う").unwrap().pitch_accent, vec![0]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Proper names (surnames, places, companies...) from JMnedict.
+//!
+//! The `jmdict` crate only embeds common vocabulary. Names are loaded at
+//! runtime from a local copy of `JMnedict.xml` (enable the `names` feature for
+//! the loader) and passed to [`crate::find_with_options`] or
+//! [`crate::find_with_nhk_options`] through [`crate::FindOptions::names`].
+
+use crate::kana_utils::katakana_to_hiragana;
+use std::collections::HashMap;
+
+/// Kind of proper name, from the JMnedict `<name_type>` entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NameType {
+    Surname,
+    Place,
+    /// Name of unclassified kind
+    Unclassified,
+    Company,
+    Product,
+    Work,
+    /// Male given name
+    Masculine,
+    /// Female given name
+    Feminine,
+    /// Full name of a particular person
+    Person,
+    /// Given name, gender not specified
+    Given,
+    /// Railway station
+    Station,
+    Organization,
+    Character,
+    Creature,
+    Deity,
+    Document,
+    Event,
+    Fiction,
+    Group,
+    Legend,
+    Mythology,
+    Object,
+    Religion,
+    Service,
+    Ship,
+    Other,
+}
+
+impl NameType {
+    /// Parse a JMnedict entity name such as `surname` (without `&` and `;`)
+    pub fn from_entity(entity: &str) -> NameType {
+        match entity {
+            "surname" => NameType::Surname,
+            "place" => NameType::Place,
+            "unclass" => NameType::Unclassified,
+            "company" => NameType::Company,
+            "product" => NameType::Product,
+            "work" => NameType::Work,
+            "masc" => NameType::Masculine,
+            "fem" => NameType::Feminine,
+            "person" => NameType::Person,
+            "given" => NameType::Given,
+            "station" => NameType::Station,
+            "organization" => NameType::Organization,
+            "char" => NameType::Character,
+            "creat" => NameType::Creature,
+            "dei" => NameType::Deity,
+            "doc" => NameType::Document,
+            "ev" => NameType::Event,
+            "fict" => NameType::Fiction,
+            "group" => NameType::Group,
+            "leg" => NameType::Legend,
+            "myth" => NameType::Mythology,
+            "obj" => NameType::Object,
+            "relig" => NameType::Religion,
+            "serv" => NameType::Service,
+            "ship" => NameType::Ship,
+            _ => NameType::Other,
+        }
+    }
+}
+
+/// One spelling and reading of a proper name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameEntry {
+    /// Kanji spelling, or the reading for kana-only names
+    pub text: String,
+    pub reading: String,
+    pub name_types: Vec<NameType>,
+}
+
+/// Proper names indexed by spelling and by reading
+#[derive(Debug, Clone, Default)]
+pub struct NameDictionary {
+    entries: Vec<NameEntry>,
+    by_text: HashMap<String, Vec<usize>>,
+    // Keyed by hiragana reading
+    by_reading: HashMap<String, Vec<usize>>,
+}
+
+impl NameDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, entry: NameEntry) {
+        let index = self.entries.len();
+        self.by_text.entry(entry.text.clone()).or_default().push(index);
+        self.by_reading.entry(katakana_to_hiragana(&entry.reading)).or_default().push(index);
+        self.entries.push(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Names spelled exactly `text`
+    pub fn with_text<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a NameEntry> + 'a {
+        self.lookup(self.by_text.get(text))
+    }
+
+    /// Names read as `reading`, in hiragana or katakana
+    pub fn with_reading<'a>(&'a self, reading: &str) -> impl Iterator<Item = &'a NameEntry> + 'a {
+        self.lookup(self.by_reading.get(&katakana_to_hiragana(reading)))
+    }
+
+    fn lookup<'a>(&'a self, indices: Option<&'a Vec<usize>>) -> impl Iterator<Item = &'a NameEntry> + 'a {
+        indices.into_iter()
+            .flatten()
+            .map(|&i| &self.entries[i])
+    }
+}
+
+#[cfg(feature = "names")]
+pub use loader::NameDictionaryError;
+
+#[cfg(feature = "names")]
+mod loader {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+    use std::fmt;
+    use std::io::BufRead;
+    use std::path::Path;
+
+    /// Failure to load a JMnedict file
+    #[derive(Debug)]
+    pub enum NameDictionaryError {
+        Io(std::io::Error),
+        Xml(quick_xml::Error),
+    }
+
+    impl fmt::Display for NameDictionaryError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                NameDictionaryError::Io(e) => write!(f, "cannot read JMnedict file: {e}"),
+                NameDictionaryError::Xml(e) => write!(f, "invalid JMnedict XML: {e}"),
+            }
+        }
+    }
+
+    impl std::error::Error for NameDictionaryError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                NameDictionaryError::Io(e) => Some(e),
+                NameDictionaryError::Xml(e) => Some(e),
+            }
+        }
+    }
+
+    impl From<std::io::Error> for NameDictionaryError {
+        fn from(e: std::io::Error) -> Self {
+            NameDictionaryError::Io(e)
+        }
+    }
+
+    impl From<quick_xml::Error> for NameDictionaryError {
+        fn from(e: quick_xml::Error) -> Self {
+            NameDictionaryError::Xml(e)
+        }
+    }
+
+    impl From<quick_xml::encoding::EncodingError> for NameDictionaryError {
+        fn from(e: quick_xml::encoding::EncodingError) -> Self {
+            NameDictionaryError::Xml(e.into())
+        }
+    }
+
+    impl NameDictionary {
+        /// Load a `JMnedict.xml` file
+        pub fn from_jmnedict_path(path: impl AsRef<Path>) -> Result<Self, NameDictionaryError> {
+            let file = std::fs::File::open(path)?;
+            Self::from_jmnedict_reader(std::io::BufReader::new(file))
+        }
+
+        /// Parse JMnedict XML held in memory
+        pub fn from_jmnedict_str(xml: &str) -> Result<Self, NameDictionaryError> {
+            Self::from_jmnedict_reader(xml.as_bytes())
+        }
+
+        /// Parse JMnedict XML from any buffered reader
+        pub fn from_jmnedict_reader(reader: impl BufRead) -> Result<Self, NameDictionaryError> {
+            let mut reader = Reader::from_reader(reader);
+            let mut buf = Vec::new();
+            let mut dictionary = NameDictionary::new();
+
+            // Fields of the <entry> being read
+            let mut kebs: Vec<String> = Vec::new();
+            // Readings with the spellings their <re_restr> limit them to
+            let mut rebs: Vec<(String, Vec<String>)> = Vec::new();
+            let mut name_types: Vec<NameType> = Vec::new();
+            // Inside <keb>, <reb>, <re_restr> or <name_type>
+            let mut in_field = false;
+            let mut text = String::new();
+
+            loop {
+                match reader.read_event_into(&mut buf)? {
+                    Event::Start(e) => {
+                        if matches!(e.name().as_ref(), b"keb" | b"reb" | b"re_restr" | b"name_type") {
+                            text.clear();
+                            in_field = true;
+                        }
+                    }
+                    Event::Text(e) if in_field => text.push_str(&e.decode()?),
+                    Event::GeneralRef(r) if in_field => {
+                        if let Some(c) = r.resolve_char_ref()? {
+                            text.push(c);
+                        } else {
+                            match r.decode()?.as_ref() {
+                                "amp" => text.push('&'),
+                                "lt" => text.push('<'),
+                                "gt" => text.push('>'),
+                                "quot" => text.push('"'),
+                                "apos" => text.push('\''),
+                                // <name_type>&surname;</name_type>
+                                entity => text.push_str(entity),
+                            }
+                        }
+                    }
+                    Event::End(e) => {
+                        in_field = false;
+                        match e.name().as_ref() {
+                            b"keb" => kebs.push(std::mem::take(&mut text)),
+                            b"reb" => rebs.push((std::mem::take(&mut text), Vec::new())),
+                            b"re_restr" => {
+                                if let Some((_, restrictions)) = rebs.last_mut() {
+                                    restrictions.push(std::mem::take(&mut text));
+                                }
+                            }
+                            b"name_type" => name_types.push(NameType::from_entity(&std::mem::take(&mut text))),
+                            b"entry" => {
+                                for (reading, restrictions) in &rebs {
+                                    let texts = if kebs.is_empty() { std::slice::from_ref(reading) } else { kebs.as_slice() };
+                                    for t in texts.iter().filter(|t| restrictions.is_empty() || restrictions.contains(t)) {
+                                        dictionary.insert(NameEntry {
+                                            text: t.clone(),
+                                            reading: reading.clone(),
+                                            name_types: name_types.clone(),
+                                        });
+                                    }
+                                }
+                                kebs.clear();
+                                rebs.clear();
+                                name_types.clear();
+                            }
+                            _ => {}
+                        }
+                    }
+                    Event::Eof => break,
+                    _ => {}
+                }
+                buf.clear();
+            }
+
+            Ok(dictionary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_text_and_reading() {
+        let mut names = NameDictionary::new();
+        names.insert(NameEntry {
+            text: "佐藤".to_string(),
+            reading: "さとう".to_string(),
+            name_types: vec![NameType::Surname],
+        });
+
+        assert_eq!(names.len(), 1);
+        assert_eq!(names.with_text("佐藤").count(), 1);
+        assert_eq!(names.with_reading("さとう").count(), 1);
+        assert_eq!(names.with_reading("サトウ").count(), 1);
+        assert_eq!(names.with_reading("かとう").count(), 0);
+    }
+
+    #[cfg(feature = "names")]
+    #[test]
+    fn test_parse_jmnedict() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMnedict [
+<!ENTITY surname "family or surname">
+<!ENTITY place "place name">
+]>
+<JMnedict>
+<entry>
+<ent_seq>5000000</ent_seq>
+<k_ele><keb>神戸</keb></k_ele>
+<r_ele><reb>こうべ</reb></r_ele>
+<trans><name_type>&place;</name_type><name_type>&surname;</name_type><trans_det>Kobe</trans_det></trans>
+</entry>
+<entry>
+<ent_seq>5000001</ent_seq>
+<r_ele><reb>さとう</reb></r_ele>
+<trans><name_type>&surname;</name_type><trans_det>Satou</trans_det></trans>
+</entry>
+<entry>
+<ent_seq>5000002</ent_seq>
+<k_ele><keb>東</keb></k_ele>
+<k_ele><keb>東風</keb></k_ele>
+<r_ele><reb>あずま</reb><re_restr>東</re_restr></r_ele>
+<r_ele><reb>こち</reb><re_restr>東風</re_restr></r_ele>
+<trans><name_type>&surname;</name_type><trans_det>Azuma</trans_det></trans>
+</entry>
+</JMnedict>"#;
+
+        let names = NameDictionary::from_jmnedict_str(xml).unwrap();
+        assert_eq!(names.len(), 4);
+
+        let kobe: Vec<_> = names.with_text("神戸").collect();
+        assert_eq!(kobe[0].reading, "こうべ");
+        assert_eq!(kobe[0].name_types, vec![NameType::Place, NameType::Surname]);
+
+        // Kana-only names use their reading as text
+        assert_eq!(names.with_reading("さとう").next().unwrap().text, "さとう");
+
+        // <re_restr> limits a reading to some spellings
+        let azuma: Vec<_> = names.with_reading("あずま").map(|n| n.text.as_str()).collect();
+        assert_eq!(azuma, vec!["東"]);
+        let kochi: Vec<_> = names.with_reading("こち").map(|n| n.text.as_str()).collect();
+        assert_eq!(kochi, vec!["東風"]);
+    }
+}
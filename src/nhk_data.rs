@@ -1,8 +1,12 @@
 // Auto-generated NHK pitch accent data with multiple pitch support
 // DO NOT EDIT - this file is generated by scripts/generate_nhk_data_multi_pitch.py
 
-/// Pitch accent data as (reading, text, pitches) tuples
-static NHK_PITCH_DATA: &[(&str, &str, &[u8])] = &[
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Pitch accent data as (reading, text, pitches) tuples, also read by the
+/// accent predictor and the affix table
+pub(crate) static NHK_PITCH_DATA: &[(&str, &str, &[u8])] = &[
     ("〜あめ", "雨", &[4]),
     ("〜あめ", "飴", &[3]),
     ("〜いん", "員", &[3]),
@@ -17224,12 +17228,21 @@ static NHK_PITCH_DATA: &[(&str, &str, &[u8])] = &[
     ("ワット", "ワット", &[1]),
 ];
 
+/// Pitch accents keyed by (reading, text), built on first use
+pub(crate) fn pitch_index() -> &'static HashMap<(&'static str, &'static str), &'static [u8]> {
+    static INDEX: OnceLock<HashMap<(&'static str, &'static str), &'static [u8]>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        NHK_PITCH_DATA
+            .iter()
+            .map(|&(reading, text, pitches)| ((reading, text), pitches))
+            .collect()
+    })
+}
+
 /// Get pitch accents for a word given its reading and text
 pub fn get_pitch_accents(reading: &str, text: &str) -> Vec<u8> {
-    NHK_PITCH_DATA
-        .iter()
-        .find(|(r, t, _)| *r == reading && *t == text)
-        .map(|(_, _, pitches)| pitches.to_vec())
+    pitch_index()
+        .get(&(reading, text))
+        .map(|pitches| pitches.to_vec())
         .unwrap_or_default()
 }
-
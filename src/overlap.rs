@@ -0,0 +1,211 @@
+//! Kanji shared between homophones, and the kanji behind each part of the reading.
+//!
+//! For こうせい, 公正, 校正 and 更正 share 正 for せい, while こう is written
+//! 構, 公, 厚, 校... Words are split with [`crate::furigana`]; kanji the
+//! reading table cannot place stay together as one span.
+
+use crate::furigana::{KanjiReadings, align_furigana_with};
+use crate::kana_utils::katakana_to_hiragana;
+use crate::pitch::mora_count;
+use crate::{HomophoneGroup, WordFrequencyWithPitch};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Kanji of a word and the morae they are read as
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KanjiSpan {
+    /// One kanji, or a run the reading table could not split
+    pub kanji: String,
+    pub reading: String,
+    /// Mora positions of `reading` in the word, from 0
+    pub morae: Range<usize>,
+}
+
+/// One homophone split into kanji spans
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WordKanji {
+    pub text: String,
+    pub spans: Vec<KanjiSpan>,
+}
+
+/// A kanji written in several of the homophones
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SharedKanji {
+    pub kanji: char,
+    /// Homophones containing it
+    pub words: Vec<String>,
+    /// Readings it has in them, empty where it is part of an unsplit run
+    pub readings: Vec<String>,
+}
+
+/// A kanji and the homophones in which it is read as some morae
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KanjiWords {
+    pub kanji: String,
+    pub words: Vec<String>,
+}
+
+/// Every way the homophones write one stretch of the reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MoraKanji {
+    pub morae: Range<usize>,
+    pub reading: String,
+    /// Most widely used kanji first
+    pub kanji: Vec<KanjiWords>,
+}
+
+/// Kanji breakdown of a set of homophones
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KanjiOverlap {
+    pub words: Vec<WordKanji>,
+    /// Kanji found in two or more words, most shared first
+    pub shared_kanji: Vec<SharedKanji>,
+    /// Kanji per stretch of morae, in reading order
+    pub by_mora: Vec<MoraKanji>,
+}
+
+impl HomophoneGroup {
+    /// Kanji shared between the group's words, with the built-in reading table
+    pub fn kanji_overlap(&self) -> KanjiOverlap {
+        kanji_overlap(&self.words, KanjiReadings::builtin())
+    }
+}
+
+/// Compare the kanji of homophones, splitting words with `table`
+///
+/// Each spelling is analysed once; kana-only words have no spans.
+pub fn kanji_overlap(words: &[WordFrequencyWithPitch], table: &KanjiReadings) -> KanjiOverlap {
+    let mut analysed: Vec<WordKanji> = Vec::new();
+    for word in words {
+        if analysed.iter().any(|w| w.text == word.text) {
+            continue;
+        }
+        let mut spans = Vec::new();
+        let mut position = 0;
+        for segment in align_furigana_with(&word.text, &word.reading, table) {
+            let reading = segment.reading.clone().unwrap_or_else(|| katakana_to_hiragana(&segment.text));
+            let morae = position..position + mora_count(&reading);
+            position = morae.end;
+            if segment.reading.is_some() {
+                spans.push(KanjiSpan { kanji: segment.text, reading, morae });
+            }
+        }
+        analysed.push(WordKanji { text: word.text.clone(), spans });
+    }
+
+    // Kanji order of first appearance, so ties keep the words' order
+    let mut shared: Vec<SharedKanji> = Vec::new();
+    for word in &analysed {
+        for span in &word.spans {
+            for kanji in span.kanji.chars() {
+                let index = match shared.iter().position(|s| s.kanji == kanji) {
+                    Some(index) => index,
+                    None => {
+                        shared.push(SharedKanji { kanji, words: Vec::new(), readings: Vec::new() });
+                        shared.len() - 1
+                    }
+                };
+                let entry = &mut shared[index];
+                if !entry.words.contains(&word.text) {
+                    entry.words.push(word.text.clone());
+                }
+                if span.kanji.chars().count() == 1 && !entry.readings.contains(&span.reading) {
+                    entry.readings.push(span.reading.clone());
+                }
+            }
+        }
+    }
+    shared.retain(|s| s.words.len() > 1);
+    shared.sort_by_key(|s| std::cmp::Reverse(s.words.len()));
+
+    let mut stretches: BTreeMap<(usize, usize), MoraKanji> = BTreeMap::new();
+    for word in &analysed {
+        for span in &word.spans {
+            let stretch = stretches.entry((span.morae.start, span.morae.end)).or_insert_with(|| MoraKanji {
+                morae: span.morae.clone(),
+                reading: span.reading.clone(),
+                kanji: Vec::new(),
+            });
+            match stretch.kanji.iter_mut().find(|k| k.kanji == span.kanji) {
+                Some(k) => k.words.push(word.text.clone()),
+                None => stretch.kanji.push(KanjiWords { kanji: span.kanji.clone(), words: vec![word.text.clone()] }),
+            }
+        }
+    }
+    let by_mora = stretches.into_values()
+        .map(|mut stretch| {
+            stretch.kanji.sort_by_key(|k| std::cmp::Reverse(k.words.len()));
+            stretch
+        })
+        .collect();
+
+    KanjiOverlap { words: analysed, shared_kanji: shared, by_mora }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MatchKind;
+
+    fn word(text: &str, reading: &str) -> WordFrequencyWithPitch {
+        WordFrequencyWithPitch::jmdict(0, text, reading, &jmdict::Priority::default(), MatchKind::SharedReading)
+    }
+
+    #[test]
+    fn test_kanji_overlap() {
+        let mut table = KanjiReadings::new();
+        for (kanji, reading) in [('構', "こう"), ('公', "こう"), ('校', "こう"), ('厚', "こう"), ('成', "せい"), ('正', "せい"), ('生', "せい")] {
+            table.insert(kanji, reading);
+        }
+        let words: Vec<_> = [("構成", "こうせい"), ("公正", "こうせい"), ("校正", "こうせい"), ("厚生", "こうせい"), ("後世", "こうせい")]
+            .iter()
+            .map(|&(text, reading)| word(text, reading))
+            .collect();
+        let overlap = kanji_overlap(&words, &table);
+
+        assert_eq!(overlap.words[0].spans[1], KanjiSpan { kanji: "成".to_string(), reading: "せい".to_string(), morae: 2..4 });
+        // 後世 is not in the table, so it stays one span
+        assert_eq!(overlap.words[4].spans.len(), 1);
+
+        assert_eq!(overlap.shared_kanji.len(), 1);
+        assert_eq!(overlap.shared_kanji[0].kanji, '正');
+        assert_eq!(overlap.shared_kanji[0].words, vec!["公正", "校正"]);
+        assert_eq!(overlap.shared_kanji[0].readings, vec!["せい"]);
+
+        let sei = overlap.by_mora.iter().find(|m| m.morae == (2..4)).unwrap();
+        assert_eq!(sei.reading, "せい");
+        assert_eq!(sei.kanji[0], KanjiWords { kanji: "正".to_string(), words: vec!["公正".to_string(), "校正".to_string()] });
+        assert!(overlap.by_mora.iter().any(|m| m.morae == (0..4) && m.kanji[0].kanji == "後世"));
+    }
+
+    #[test]
+    fn test_group_overlap() {
+        let groups = crate::homophone_groups();
+        let kousei = groups.iter().find(|g| g.reading == "こうせい").unwrap();
+        let overlap = kousei.kanji_overlap();
+        // 正 appears in 公正, 校正 and the uncommon 更正
+        let sei = overlap.shared_kanji.iter().find(|s| s.kanji == '正').unwrap();
+        assert_eq!(sei.words.len(), if cfg!(feature = "uncommon") { 3 } else { 2 });
+
+        // The built-in table splits words kanji by kanji
+        let kanji_at = |morae: Range<usize>| -> Vec<String> {
+            let stretch = overlap.by_mora.iter().find(|m| m.morae == morae).unwrap();
+            stretch.kanji.iter().map(|k| k.kanji.clone()).collect()
+        };
+        let kou = kanji_at(0..2);
+        for kanji in ["構", "公", "厚"] {
+            assert!(kou.iter().any(|k| k == kanji), "{kanji} in {kou:?}");
+        }
+        let sei = kanji_at(2..4);
+        for kanji in ["成", "正", "生"] {
+            assert!(sei.iter().any(|k| k == kanji), "{kanji} in {sei:?}");
+        }
+        assert_eq!(overlap.by_mora.iter().find(|m| m.morae == (2..4)).unwrap().reading, "せい");
+    }
+}
@@ -0,0 +1,138 @@
+/// Pitch of a single mora
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Pitch {
+    Low,
+    High,
+}
+
+/// Accent pattern class of a word, derived from its NHK accent number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AccentType {
+    /// [0]: no downstep, a following particle stays high
+    Heiban,
+    /// [1]: downstep after the first mora
+    Atamadaka,
+    /// [2..n-1]: downstep inside the word
+    Nakadaka,
+    /// [n]: downstep after the last mora, so only a following particle is low
+    Odaka,
+}
+
+/// Check if a kana attaches to the previous one to form a single mora (ゃ, ゅ, ょ, ぁ...)
+pub fn is_small_kana(c: char) -> bool {
+    matches!(
+        c,
+        'ゃ' | 'ゅ' | 'ょ' | 'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'ゎ'
+            | 'ャ' | 'ュ' | 'ョ' | 'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ヮ'
+    )
+}
+
+/// Split a kana reading into morae
+///
+/// Small kana attach to the preceding kana (きょ, ファ), while っ, ん and ー
+/// each count as a mora of their own.
+pub fn split_morae(reading: &str) -> Vec<String> {
+    let mut morae: Vec<String> = Vec::new();
+
+    for c in reading.chars() {
+        match morae.last_mut() {
+            Some(last) if is_small_kana(c) => last.push(c),
+            _ => morae.push(c.to_string()),
+        }
+    }
+
+    morae
+}
+
+/// Number of morae in a kana reading
+pub fn mora_count(reading: &str) -> usize {
+    reading.chars().filter(|&c| !is_small_kana(c)).count()
+}
+
+/// Accent type for an NHK accent number on a word with `morae` morae
+pub fn accent_type(accent: u8, morae: usize) -> AccentType {
+    match accent as usize {
+        0 => AccentType::Heiban,
+        1 => AccentType::Atamadaka,
+        n if n >= morae => AccentType::Odaka,
+        _ => AccentType::Nakadaka,
+    }
+}
+
+/// Pitch of each mora of `reading` for an NHK accent number, followed by the
+/// pitch of a trailing particle (が, は...)
+///
+/// The result has `mora_count(reading) + 1` elements.
+pub fn pitch_pattern(reading: &str, accent: u8) -> Vec<Pitch> {
+    let morae = mora_count(reading);
+    let accent = accent as usize;
+
+    (0..=morae)
+        .map(|i| {
+            let high = match accent {
+                // Heiban: low start, then high through the particle
+                0 => i > 0,
+                // Atamadaka: only the first mora is high
+                1 => i == 0,
+                // Nakadaka/odaka: low start, high up to the accent nucleus
+                n => i > 0 && i < n,
+            };
+            if high { Pitch::High } else { Pitch::Low }
+        })
+        .collect()
+}
+
+/// Pitch pattern as a string of `H` and `L`, e.g. `LHHHH` for こうせい[0]
+///
+/// The last letter is the trailing particle.
+pub fn pitch_pattern_string(reading: &str, accent: u8) -> String {
+    pitch_pattern(reading, accent)
+        .into_iter()
+        .map(|p| match p {
+            Pitch::High => 'H',
+            Pitch::Low => 'L',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_morae() {
+        assert_eq!(split_morae("こうせい"), vec!["こ", "う", "せ", "い"]);
+        assert_eq!(split_morae("きょうと"), vec!["きょ", "う", "と"]);
+        assert_eq!(split_morae("がっこう"), vec!["が", "っ", "こ", "う"]);
+        assert_eq!(split_morae("ファイル"), vec!["ファ", "イ", "ル"]);
+        assert_eq!(split_morae("ルール"), vec!["ル", "ー", "ル"]);
+        assert_eq!(mora_count("しゅっちょう"), 4);
+    }
+
+    #[test]
+    fn test_pitch_pattern() {
+        // 構成[0], 後世[1], 橋[2], 箸[1]
+        assert_eq!(pitch_pattern_string("こうせい", 0), "LHHHH");
+        assert_eq!(pitch_pattern_string("こうせい", 1), "HLLLL");
+        assert_eq!(pitch_pattern_string("はし", 2), "LHL");
+        assert_eq!(pitch_pattern_string("はし", 1), "HLL");
+        assert_eq!(pitch_pattern_string("はし", 0), "LHH");
+        // Odaka: the drop falls on the particle
+        assert_eq!(pitch_pattern_string("おとうと", 4), "LHHHL");
+        // Nakadaka
+        assert_eq!(pitch_pattern_string("あなた", 2), "LHLL");
+        // One-mora heiban: only the particle is high
+        assert_eq!(pitch_pattern_string("ひ", 0), "LH");
+    }
+
+    #[test]
+    fn test_accent_type() {
+        assert_eq!(accent_type(0, 2), AccentType::Heiban);
+        assert_eq!(accent_type(1, 2), AccentType::Atamadaka);
+        assert_eq!(accent_type(2, 2), AccentType::Odaka);
+        assert_eq!(accent_type(2, 3), AccentType::Nakadaka);
+        assert_eq!(accent_type(1, 1), AccentType::Atamadaka);
+    }
+}
@@ -0,0 +1,285 @@
+//! Accent prediction for words without NHK data.
+//!
+//! Predictions come from rules of Tokyo Japanese accent, with per-class
+//! defaults learned from the NHK table:
+//!
+//! 1. Katakana loanwords take the antepenultimate rule (クリ'スマス, コンピュ'ーター).
+//! 2. Nouns that split into two NHK words follow the compound noun rules, or
+//!    the rule of the final element if NHK lists it as a suffix.
+//! 3. Other nouns ending in an NHK suffix take its rule (大雨 from 〜あめ),
+//!    see [`crate::affix`].
+//! 4. Otherwise a word takes the accent most NHK words of its class (noun,
+//!    verb, i-adjective) and mora count have. Verbs and i-adjectives come out
+//!    heiban or accented on the penultimate mora of the dictionary form.
+
+use crate::affix::{suffix_compound_accent, suffix_rule};
+use crate::nhk_data::{NHK_PITCH_DATA, pitch_index};
+use crate::pitch::split_morae;
+use crate::{PitchSource, WordFrequencyWithPitch};
+use jmdict::Enum;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Which rule produced a predicted accent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PredictionRule {
+    /// Katakana loanword, accent on the antepenultimate mora
+    Loanword,
+    /// Noun ending in a suffix with an NHK compound accent rule
+    Affix,
+    /// Compound noun, from the accent of its final element
+    Compound,
+    /// Most common accent of NHK verbs with as many morae
+    Verb,
+    /// Most common accent of NHK i-adjectives with as many morae
+    Adjective,
+    /// Most common accent of NHK nouns (and other words) with as many morae
+    Noun,
+}
+
+/// An estimated accent, see [`predict_accent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PitchPrediction {
+    pub accent: u8,
+    pub rule: PredictionRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum WordClass {
+    Noun,
+    Verb,
+    Adjective,
+}
+
+impl WordClass {
+    fn from_parts_of_speech<'a>(codes: impl IntoIterator<Item = &'a str>) -> WordClass {
+        let mut class = WordClass::Noun;
+        for code in codes {
+            if code == "adj-i" || code == "adj-ix" {
+                return WordClass::Adjective;
+            }
+            if code.starts_with("v5") || code.starts_with("v1") || matches!(code, "vk" | "vz" | "vs-i" | "vs-s") {
+                class = WordClass::Verb;
+            }
+        }
+        class
+    }
+
+    fn rule(self) -> PredictionRule {
+        match self {
+            WordClass::Noun => PredictionRule::Noun,
+            WordClass::Verb => PredictionRule::Verb,
+            WordClass::Adjective => PredictionRule::Adjective,
+        }
+    }
+}
+
+/// Predict the accent of a word from its spelling, reading and JMDict part-of-speech codes
+///
+/// Pass the codes of the word's senses (`n`, `v5u`, `adj-i`...), or none if
+/// unknown. Returns `None` only for an empty reading.
+pub fn predict_accent(text: &str, reading: &str, parts_of_speech: &[&str]) -> Option<PitchPrediction> {
+    let morae = split_morae(reading);
+    if morae.is_empty() {
+        return None;
+    }
+
+    if is_loanword(text) {
+        return Some(PitchPrediction { accent: loanword_accent(&morae), rule: PredictionRule::Loanword });
+    }
+
+    let class = WordClass::from_parts_of_speech(parts_of_speech.iter().copied());
+    if class == WordClass::Noun {
+        if let Some(prediction) = compound_accent(text, reading) {
+            return Some(prediction);
+        }
+        if let Some(accent) = suffix_compound_accent(text, reading) {
+            return Some(PitchPrediction { accent, rule: PredictionRule::Affix });
+        }
+    }
+
+    Some(PitchPrediction { accent: class_accent(class, morae.len()), rule: class.rule() })
+}
+
+/// Predict the accent of one spelling and reading of a JMDict entry
+fn predict_for_entry(text: &str, reading: &str, entry: Option<&jmdict::Entry>) -> Option<PitchPrediction> {
+    let codes: Vec<&str> = entry.into_iter()
+        .flat_map(|e| e.senses())
+        .flat_map(|s| s.parts_of_speech())
+        .map(|p| p.code())
+        .collect();
+    predict_accent(text, reading, &codes)
+}
+
+/// Predict the accent of every word without pitch data
+pub(crate) fn fill_missing_pitches(words: &mut [WordFrequencyWithPitch]) {
+    let needed: HashSet<u32> = words.iter()
+        .filter(|w| w.pitch_accent.is_empty())
+        .filter_map(|w| w.jmdict_entry)
+        .collect();
+    let entries: HashMap<u32, jmdict::Entry> = if needed.is_empty() {
+        HashMap::new()
+    } else {
+        jmdict::entries().filter(|e| needed.contains(&e.number)).map(|e| (e.number, e)).collect()
+    };
+
+    for word in words.iter_mut().filter(|w| w.pitch_accent.is_empty()) {
+        let entry = word.jmdict_entry.and_then(|n| entries.get(&n));
+        if let Some(prediction) = predict_for_entry(&word.text, &word.reading, entry) {
+            word.pitch_accent = vec![prediction.accent];
+            word.pitch_source = PitchSource::Predicted { rule: prediction.rule };
+        }
+    }
+}
+
+fn is_loanword(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| matches!(c, 'ァ'..='ヶ' | 'ー'))
+}
+
+/// Downstep after the antepenultimate mora, moved back off ー, ン and ッ
+fn loanword_accent(morae: &[String]) -> u8 {
+    if morae.len() <= 2 {
+        return 1;
+    }
+    let mut accent = morae.len() - 2;
+    while accent > 1 && matches!(morae[accent - 1].as_str(), "ー" | "ン" | "ッ" | "ん" | "っ") {
+        accent -= 1;
+    }
+    accent as u8
+}
+
+/// Accent of text+reading as a compound of two NHK words
+///
+/// The final element decides: with 1-2 morae the downstep falls on the last
+/// mora of the first element; with 3-4 morae on the first mora of the final
+/// element, unless that element is nakadaka and keeps its accent; with 5 or
+/// more the final element keeps its accent, or the compound is heiban. A final
+/// element NHK lists as a suffix follows its own rule instead.
+fn compound_accent(text: &str, reading: &str) -> Option<PitchPrediction> {
+    // JMDict readings never start with 〜, so affix entries do not match
+    let index = pitch_index();
+    let text_splits = text.char_indices().skip(1).map(|(i, _)| i);
+
+    // Longest final element first
+    for t in text_splits {
+        let (first_text, last_text) = text.split_at(t);
+        for (r, _) in reading.char_indices().skip(1) {
+            let (first_reading, last_reading) = reading.split_at(r);
+            if !index.contains_key(&(first_reading, first_text)) {
+                continue;
+            }
+            let Some(&last_accents) = index.get(&(last_reading, last_text)) else {
+                continue;
+            };
+
+            let n1 = split_morae(first_reading).len();
+            if let Some(rule) = suffix_rule(last_reading, last_text) {
+                return Some(PitchPrediction { accent: rule.compound_accent(n1), rule: PredictionRule::Affix });
+            }
+            let n2 = split_morae(last_reading).len();
+            let a2 = last_accents.first().copied().unwrap_or(0) as usize;
+            let accent = match n2 {
+                1 | 2 => n1,
+                3 | 4 if a2 == 0 || a2 >= n2 => n1 + 1,
+                3 | 4 => n1 + a2,
+                _ if a2 == 0 => 0,
+                _ => n1 + a2,
+            };
+            return Some(PitchPrediction { accent: accent as u8, rule: PredictionRule::Compound });
+        }
+    }
+    None
+}
+
+fn class_accent(class: WordClass, morae: usize) -> u8 {
+    if let Some(&accent) = class_defaults().get(&(class, morae)) {
+        return accent;
+    }
+    match class {
+        WordClass::Noun => 0,
+        WordClass::Verb | WordClass::Adjective => morae.saturating_sub(1) as u8,
+    }
+}
+
+/// Most common first NHK accent per word class and mora count
+fn class_defaults() -> &'static HashMap<(WordClass, usize), u8> {
+    static DEFAULTS: OnceLock<HashMap<(WordClass, usize), u8>> = OnceLock::new();
+    DEFAULTS.get_or_init(|| {
+        let mut classes: HashMap<&'static str, WordClass> = HashMap::new();
+        for entry in jmdict::entries() {
+            let class = WordClass::from_parts_of_speech(
+                entry.senses().flat_map(|s| s.parts_of_speech()).map(|p| p.code())
+            );
+            for kanji in entry.kanji_elements() {
+                classes.entry(kanji.text).or_insert(class);
+            }
+            for reading in entry.reading_elements() {
+                classes.entry(reading.text).or_insert(class);
+            }
+        }
+
+        let mut counts: HashMap<(WordClass, usize, u8), usize> = HashMap::new();
+        for &(reading, text, accents) in NHK_PITCH_DATA {
+            if let (Some(&class), Some(&accent)) = (classes.get(text), accents.first())
+                && !reading.starts_with('〜')
+            {
+                *counts.entry((class, split_morae(reading).len(), accent)).or_default() += 1;
+            }
+        }
+
+        let mut best: HashMap<(WordClass, usize), (u8, usize)> = HashMap::new();
+        for ((class, morae, accent), count) in counts {
+            let current = best.entry((class, morae)).or_insert((accent, 0));
+            // Ties go to the lower accent so the result does not depend on hash order
+            if count > current.1 || (count == current.1 && accent < current.0) {
+                *current = (accent, count);
+            }
+        }
+        best.into_iter().map(|(key, (accent, _))| (key, accent)).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loanword() {
+        let christmas = predict_accent("クリスマス", "くりすます", &["n"]).unwrap();
+        assert_eq!(christmas, PitchPrediction { accent: 3, rule: PredictionRule::Loanword });
+        // The antepenultimate ー moves the downstep back to ピュ
+        assert_eq!(predict_accent("コンピューター", "こんぴゅーたー", &[]).unwrap().accent, 3);
+        assert_eq!(predict_accent("パン", "ぱん", &[]).unwrap().accent, 1);
+    }
+
+    #[test]
+    fn test_compound() {
+        // 九州 + 病院 (heiban, 4 morae): downstep on the first mora of 病院
+        let prediction = predict_accent("九州病院", "きゅうしゅうびょういん", &["n"]).unwrap();
+        assert_eq!(prediction, PitchPrediction { accent: 5, rule: PredictionRule::Compound });
+        // 先生 (4 morae, nakadaka) keeps its own downstep
+        assert_eq!(predict_accent("九州先生", "きゅうしゅうせんせい", &["n"]).unwrap().accent, 7);
+    }
+
+    #[test]
+    fn test_affix() {
+        // 〜あめ (雨): downstep after あ
+        let heavy_rain = predict_accent("大雨", "おおあめ", &["n"]).unwrap();
+        assert_eq!(heavy_rain, PitchPrediction { accent: 3, rule: PredictionRule::Affix });
+    }
+
+    #[test]
+    fn test_verb_and_adjective() {
+        let verb = predict_accent("ほげる", "ほげる", &["v1", "vt"]).unwrap();
+        assert_eq!(verb.rule, PredictionRule::Verb);
+        assert!(verb.accent == 0 || verb.accent == 2);
+
+        let adjective = predict_accent("ほげい", "ほげい", &["adj-i"]).unwrap();
+        assert_eq!(adjective.rule, PredictionRule::Adjective);
+        assert!(adjective.accent == 0 || adjective.accent == 2);
+
+        assert_eq!(predict_accent("", "", &[]), None);
+    }
+}
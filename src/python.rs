@@ -0,0 +1,310 @@
+//! Python bindings, enabled with the `python` feature.
+//!
+//! Build the extension module with `maturin develop` (see `pyproject.toml`).
+//! Results are frozen classes with read-only attributes, `__repr__` and `__eq__`,
+//! so they behave like Python dataclasses.
+
+use pyo3::prelude::*;
+
+use crate::pitch::{self, AccentType};
+use crate::{FindWithNhkResult, HomophoneGroup, MatchKind, WordFrequency, WordFrequencyWithPitch};
+
+/// A homophone without pitch accent data
+#[pyclass(name = "Word", module = "jaydar", frozen, eq, get_all)]
+#[derive(Clone, PartialEq)]
+pub struct PyWord {
+    text: String,
+    reading: String,
+    frequency_score: u32,
+    is_common: bool,
+    match_kind: &'static str,
+}
+
+#[pymethods]
+impl PyWord {
+    fn __repr__(&self) -> String {
+        format!(
+            "Word(text='{}', reading='{}', frequency_score={}, is_common={}, match_kind='{}')",
+            self.text, self.reading, self.frequency_score, py_bool(self.is_common), self.match_kind,
+        )
+    }
+}
+
+impl From<WordFrequency> for PyWord {
+    fn from(w: WordFrequency) -> Self {
+        PyWord {
+            text: w.text,
+            reading: w.reading,
+            frequency_score: w.frequency_score,
+            is_common: w.is_common,
+            match_kind: match_kind_name(w.match_kind),
+        }
+    }
+}
+
+/// A homophone with its NHK pitch accents
+#[pyclass(name = "WordWithPitch", module = "jaydar", frozen, eq, get_all)]
+#[derive(Clone, PartialEq)]
+pub struct PyWordWithPitch {
+    text: String,
+    reading: String,
+    frequency_score: u32,
+    is_common: bool,
+    // Not Vec<u8>, which pyo3 would expose as `bytes` instead of a list
+    pitch_accent: Vec<u32>,
+    match_kind: &'static str,
+}
+
+#[pymethods]
+impl PyWordWithPitch {
+    fn __repr__(&self) -> String {
+        format!(
+            "WordWithPitch(text='{}', reading='{}', frequency_score={}, is_common={}, pitch_accent={:?}, match_kind='{}')",
+            self.text, self.reading, self.frequency_score, py_bool(self.is_common), self.pitch_accent, self.match_kind,
+        )
+    }
+}
+
+impl From<WordFrequencyWithPitch> for PyWordWithPitch {
+    fn from(w: WordFrequencyWithPitch) -> Self {
+        PyWordWithPitch {
+            text: w.text,
+            reading: w.reading,
+            frequency_score: w.frequency_score,
+            is_common: w.is_common,
+            pitch_accent: w.pitch_accent.into_iter().map(u32::from).collect(),
+            match_kind: match_kind_name(w.match_kind),
+        }
+    }
+}
+
+/// Result of `find_with_nhk`
+///
+/// `kind` is the Rust variant name. Lists that do not apply to the variant are empty.
+#[pyclass(name = "FindWithNhkResult", module = "jaydar", frozen, eq, get_all)]
+#[derive(Clone, PartialEq)]
+pub struct PyFindWithNhkResult {
+    kind: &'static str,
+    true_homophones: Vec<PyWordWithPitch>,
+    different_pitch_homophones: Vec<PyWordWithPitch>,
+    homophones: Vec<PyWordWithPitch>,
+    target_readings: Vec<String>,
+}
+
+#[pymethods]
+impl PyFindWithNhkResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "FindWithNhkResult(kind='{}', true_homophones={}, different_pitch_homophones={}, homophones={}, target_readings={})",
+            self.kind,
+            repr_list(&self.true_homophones, PyWordWithPitch::__repr__),
+            repr_list(&self.different_pitch_homophones, PyWordWithPitch::__repr__),
+            repr_list(&self.homophones, PyWordWithPitch::__repr__),
+            repr_list(&self.target_readings, |r| format!("'{r}'")),
+        )
+    }
+}
+
+impl From<FindWithNhkResult> for PyFindWithNhkResult {
+    fn from(result: FindWithNhkResult) -> Self {
+        let convert = |words: Vec<WordFrequencyWithPitch>| words.into_iter().map(Into::into).collect();
+        match result {
+            FindWithNhkResult::NotFound => PyFindWithNhkResult {
+                kind: "NotFound",
+                true_homophones: Vec::new(),
+                different_pitch_homophones: Vec::new(),
+                homophones: Vec::new(),
+                target_readings: Vec::new(),
+            },
+            FindWithNhkResult::NoHomophones => PyFindWithNhkResult {
+                kind: "NoHomophones",
+                true_homophones: Vec::new(),
+                different_pitch_homophones: Vec::new(),
+                homophones: Vec::new(),
+                target_readings: Vec::new(),
+            },
+            FindWithNhkResult::Affix { .. } => PyFindWithNhkResult {
+                kind: "Affix",
+                true_homophones: Vec::new(),
+                different_pitch_homophones: Vec::new(),
+                homophones: Vec::new(),
+                target_readings: Vec::new(),
+            },
+            FindWithNhkResult::UniqueMatch { true_homophones, different_pitch_homophones, target_readings, .. } => PyFindWithNhkResult {
+                kind: "UniqueMatch",
+                true_homophones: convert(true_homophones),
+                different_pitch_homophones: convert(different_pitch_homophones),
+                homophones: Vec::new(),
+                target_readings,
+            },
+            FindWithNhkResult::MultipleMatches { homophones, target_readings, .. } => PyFindWithNhkResult {
+                kind: "MultipleMatches",
+                true_homophones: Vec::new(),
+                different_pitch_homophones: Vec::new(),
+                homophones: convert(homophones),
+                target_readings,
+            },
+        }
+    }
+}
+
+/// All words sharing one reading
+#[pyclass(name = "HomophoneGroup", module = "jaydar", frozen, eq, get_all)]
+#[derive(Clone, PartialEq)]
+pub struct PyHomophoneGroup {
+    reading: String,
+    words: Vec<PyWordWithPitch>,
+}
+
+#[pymethods]
+impl PyHomophoneGroup {
+    fn __repr__(&self) -> String {
+        format!(
+            "HomophoneGroup(reading='{}', words={})",
+            self.reading,
+            repr_list(&self.words, PyWordWithPitch::__repr__),
+        )
+    }
+
+    fn __len__(&self) -> usize {
+        self.words.len()
+    }
+}
+
+impl From<HomophoneGroup> for PyHomophoneGroup {
+    fn from(group: HomophoneGroup) -> Self {
+        PyHomophoneGroup {
+            reading: group.reading,
+            words: group.words.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+fn match_kind_name(kind: MatchKind) -> &'static str {
+    match kind {
+        MatchKind::KanjiElement => "kanji_element",
+        MatchKind::Reading => "reading",
+        MatchKind::KatakanaConversion => "katakana_conversion",
+        MatchKind::KatakanaSelfEntry => "katakana_self_entry",
+        MatchKind::SharedReading => "shared_reading",
+    }
+}
+
+fn py_bool(b: bool) -> &'static str {
+    if b { "True" } else { "False" }
+}
+
+fn repr_list<T>(items: &[T], repr: fn(&T) -> String) -> String {
+    format!("[{}]", items.iter().map(repr).collect::<Vec<_>>().join(", "))
+}
+
+/// Find homophones without pitch accent data
+#[pyfunction]
+fn find(word: &str) -> Vec<PyWord> {
+    crate::find(word).into_iter().map(Into::into).collect()
+}
+
+/// Find homophones with pitch accent data
+#[pyfunction]
+fn find_with_nhk(word: &str) -> PyFindWithNhkResult {
+    crate::find_with_nhk(word).into()
+}
+
+/// Every homophone group in the dictionary, ordered by reading
+#[pyfunction]
+fn homophone_groups() -> Vec<PyHomophoneGroup> {
+    crate::homophone_groups().into_iter().map(Into::into).collect()
+}
+
+/// Split a kana reading into morae
+#[pyfunction]
+fn split_morae(reading: &str) -> Vec<String> {
+    pitch::split_morae(reading)
+}
+
+/// Pitch pattern as `H`/`L` letters, the last one being a trailing particle
+#[pyfunction]
+fn pitch_pattern(reading: &str, accent: u8) -> String {
+    pitch::pitch_pattern_string(reading, accent)
+}
+
+/// Accent type name: "heiban", "atamadaka", "nakadaka" or "odaka"
+#[pyfunction]
+fn accent_type(reading: &str, accent: u8) -> &'static str {
+    match pitch::accent_type(accent, pitch::mora_count(reading)) {
+        AccentType::Heiban => "heiban",
+        AccentType::Atamadaka => "atamadaka",
+        AccentType::Nakadaka => "nakadaka",
+        AccentType::Odaka => "odaka",
+    }
+}
+
+#[pymodule]
+fn jaydar(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWord>()?;
+    m.add_class::<PyWordWithPitch>()?;
+    m.add_class::<PyFindWithNhkResult>()?;
+    m.add_class::<PyHomophoneGroup>()?;
+    m.add_function(wrap_pyfunction!(find, m)?)?;
+    m.add_function(wrap_pyfunction!(find_with_nhk, m)?)?;
+    m.add_function(wrap_pyfunction!(homophone_groups, m)?)?;
+    m.add_function(wrap_pyfunction!(split_morae, m)?)?;
+    m.add_function(wrap_pyfunction!(pitch_pattern, m)?)?;
+    m.add_function(wrap_pyfunction!(accent_type, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    fn run(code: &str) {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "jaydar").unwrap();
+            jaydar(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("jaydar", module).unwrap();
+            let code = std::ffi::CString::new(code).unwrap();
+            py.run(&code, Some(&globals), None).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_find_from_python() {
+        run(r#"
+words = jaydar.find("かう")
+texts = [w.text for w in words]
+assert "買う" in texts and "飼う" in texts
+assert words[0].frequency_score >= words[-1].frequency_score
+assert repr(words[0]).startswith("Word(text=")
+"#);
+    }
+
+    #[test]
+    fn test_find_with_nhk_from_python() {
+        run(r#"
+result = jaydar.find_with_nhk("構成")
+assert result.kind == "UniqueMatch"
+kousei = next(w for w in result.true_homophones if w.text == "構成")
+assert kousei.pitch_accent == [0]
+assert kousei.match_kind == "kanji_element"
+assert result.target_readings == ["こうせい"]
+assert result.homophones == []
+assert jaydar.find_with_nhk("こうせい").kind == "MultipleMatches"
+assert jaydar.find_with_nhk("hello").kind == "NotFound"
+"#);
+    }
+
+    #[test]
+    fn test_pitch_helpers_and_groups_from_python() {
+        run(r#"
+assert jaydar.split_morae("きょう") == ["きょ", "う"]
+assert jaydar.pitch_pattern("はし", 2) == "LHL"
+assert jaydar.accent_type("はし", 2) == "odaka"
+groups = jaydar.homophone_groups()
+assert any(g.reading == "こうせい" and len(g) > 1 for g in groups)
+"#);
+    }
+}
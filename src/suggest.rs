@@ -0,0 +1,154 @@
+//! Prefix search over readings and spellings, for autocomplete.
+//!
+//! Every JMDict reading (in hiragana) and spelling is a key in one sorted
+//! list, built on first use. A prefix is found by binary search, so a
+//! keystroke costs a lookup and a walk over the matching keys, not a scan of
+//! the dictionary.
+
+use crate::{FindOptions, calculate_frequency_score};
+use crate::kana_utils::katakana_to_hiragana;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// A word whose reading or spelling starts with the typed text
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Suggestion {
+    pub text: String,
+    /// Reading in hiragana
+    pub reading: String,
+    pub frequency_score: u32,
+    pub is_common: bool,
+    pub jmdict_entry: u32,
+    /// JMDict words read the same way, this one included; 1 if it has no homophones
+    pub homophone_count: usize,
+}
+
+struct Word {
+    text: &'static str,
+    reading: String,
+    frequency_score: u32,
+    is_common: bool,
+    entry: u32,
+}
+
+struct PrefixIndex {
+    words: Vec<Word>,
+    /// Readings and spellings with the word they belong to, sorted
+    keys: Vec<(String, usize)>,
+    /// Distinct entries per reading
+    homophones: HashMap<String, usize>,
+}
+
+fn index() -> &'static PrefixIndex {
+    static INDEX: OnceLock<PrefixIndex> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut words = Vec::new();
+        let mut keys = Vec::new();
+        let mut entries_by_reading: HashMap<String, HashSet<u32>> = HashMap::new();
+
+        for entry in jmdict::entries() {
+            for reading in entry.reading_elements() {
+                let hiragana = katakana_to_hiragana(reading.text);
+                entries_by_reading.entry(hiragana.clone()).or_default().insert(entry.number);
+
+                for (text, priority) in FindOptions::default().spellings(&entry, &reading) {
+                    let i = words.len();
+                    keys.push((hiragana.clone(), i));
+                    if text != hiragana {
+                        keys.push((text.to_string(), i));
+                    }
+                    words.push(Word {
+                        text,
+                        reading: hiragana.clone(),
+                        frequency_score: calculate_frequency_score(&priority),
+                        is_common: priority.is_common(),
+                        entry: entry.number,
+                    });
+                }
+            }
+        }
+
+        keys.sort();
+        let homophones = entries_by_reading.into_iter().map(|(reading, entries)| (reading, entries.len())).collect();
+        PrefixIndex { words, keys, homophones }
+    })
+}
+
+/// Words whose reading or spelling starts with `prefix`, most frequent first
+///
+/// Katakana is matched as hiragana, so コウセ finds 構成. Each spelling of a
+/// word is one suggestion. Returns at most `limit` words, none for an empty
+/// prefix.
+pub fn suggest(prefix: &str, limit: usize) -> Vec<Suggestion> {
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let index = index();
+    let hiragana = katakana_to_hiragana(prefix);
+
+    let mut matches: HashSet<usize> = HashSet::new();
+    for query in [prefix, hiragana.as_str()] {
+        let start = index.keys.partition_point(|(key, _)| key.as_str() < query);
+        matches.extend(index.keys[start..].iter().take_while(|(key, _)| key.starts_with(query)).map(|&(_, i)| i));
+    }
+
+    let mut words: Vec<&Word> = matches.into_iter().map(|i| &index.words[i]).collect();
+    // Ties go to shorter words, then dictionary order, so results are stable
+    words.sort_by(|a, b| {
+        b.frequency_score.cmp(&a.frequency_score)
+            .then(a.reading.len().cmp(&b.reading.len()))
+            .then(a.reading.cmp(&b.reading))
+            .then(a.text.cmp(b.text))
+    });
+    words.into_iter()
+        .take(limit)
+        .map(|w| Suggestion {
+            text: w.text.to_string(),
+            reading: w.reading.clone(),
+            frequency_score: w.frequency_score,
+            is_common: w.is_common,
+            jmdict_entry: w.entry,
+            homophone_count: index.homophones.get(&w.reading).copied().unwrap_or(1),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        let results = suggest("こうせ", 20);
+        let kousei = results.iter().find(|s| s.text == "構成").unwrap();
+        assert_eq!(kousei.reading, "こうせい");
+        if cfg!(feature = "uncommon") {
+            assert_eq!(kousei.homophone_count, 8);
+        }
+        assert!(results.iter().all(|s| s.reading.starts_with("こうせ")));
+        for i in 1..results.len() {
+            assert!(results[i - 1].frequency_score >= results[i].frequency_score);
+        }
+
+        assert_eq!(suggest("コウセ", 20), results);
+        assert_eq!(suggest("こうせ", 2).len(), 2);
+        assert!(suggest("", 10).is_empty());
+
+        // Spellings and katakana words
+        assert!(suggest("構", 5).iter().any(|s| s.text == "構成"));
+        assert!(suggest("タピ", 5).iter().any(|s| s.text == "タピオカ" && s.homophone_count == 1));
+    }
+
+    #[test]
+    fn test_suggest_pairs_like_find() {
+        for reading in ["かき", "こうべ", "きれい"] {
+            let suggested: HashSet<String> = suggest(reading, 50).into_iter()
+                .filter(|s| s.reading == reading)
+                .map(|s| s.text)
+                .collect();
+            let found: HashSet<String> = crate::find(reading).into_iter().map(|w| w.text).collect();
+            assert_eq!(suggested, found, "{reading}");
+        }
+    }
+}
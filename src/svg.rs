@@ -0,0 +1,117 @@
+//! Pitch accent diagrams as SVG.
+//!
+//! Drawn the way dictionaries do: a dot above each mora, high or low, joined
+//! by lines, and a hollow dot for the following particle.
+
+use crate::WordFrequencyWithPitch;
+use crate::pitch::{Pitch, pitch_pattern, split_morae};
+
+const MORA_WIDTH: u32 = 30;
+const HIGH_Y: u32 = 10;
+const LOW_Y: u32 = 30;
+const TEXT_Y: u32 = 56;
+const HEIGHT: u32 = 64;
+const DOT_RADIUS: u32 = 4;
+/// Space between diagrams of alternative accents
+const GAP: u32 = 20;
+
+/// Diagram of `reading` with one NHK accent number
+pub fn pitch_svg(reading: &str, accent: u8) -> String {
+    pitch_svg_alternatives(reading, &[accent])
+}
+
+/// Diagrams of `reading` with each accent, side by side in one SVG
+pub fn pitch_svg_alternatives(reading: &str, accents: &[u8]) -> String {
+    let morae = split_morae(reading);
+    let diagram_width = (morae.len() as u32 + 1) * MORA_WIDTH;
+    let width = (diagram_width * accents.len() as u32 + GAP * accents.len().saturating_sub(1) as u32).max(1);
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{HEIGHT}" viewBox="0 0 {width} {HEIGHT}">"#
+    );
+    for (i, &accent) in accents.iter().enumerate() {
+        let left = i as u32 * (diagram_width + GAP);
+        svg.push_str(&diagram(&morae, reading, accent, left));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn diagram(morae: &[String], reading: &str, accent: u8, left: u32) -> String {
+    let points: Vec<(u32, u32)> = pitch_pattern(reading, accent)
+        .into_iter()
+        .enumerate()
+        .map(|(i, pitch)| {
+            let x = left + i as u32 * MORA_WIDTH + MORA_WIDTH / 2;
+            let y = if pitch == Pitch::High { HIGH_Y } else { LOW_Y };
+            (x, y)
+        })
+        .collect();
+
+    let mut group = format!(r#"<g class="accent-{accent}">"#);
+    let polyline: Vec<String> = points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+    group.push_str(&format!(
+        r#"<polyline points="{}" fill="none" stroke="black" stroke-width="1.5"/>"#,
+        polyline.join(" ")
+    ));
+    for (i, &(x, y)) in points.iter().enumerate() {
+        // The last point is the particle
+        let fill = if i == morae.len() { "white" } else { "black" };
+        group.push_str(&format!(
+            r#"<circle cx="{x}" cy="{y}" r="{DOT_RADIUS}" fill="{fill}" stroke="black" stroke-width="1.5"/>"#
+        ));
+    }
+    for (mora, &(x, _)) in morae.iter().zip(&points) {
+        group.push_str(&format!(
+            r#"<text x="{x}" y="{TEXT_Y}" font-size="14" text-anchor="middle">{}</text>"#,
+            escape_xml(mora)
+        ));
+    }
+    group.push_str("</g>");
+    group
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl WordFrequencyWithPitch {
+    /// Diagrams of every accent in `pitch_accent`, side by side, `None` without pitch data
+    pub fn pitch_svg(&self) -> Option<String> {
+        (!self.pitch_accent.is_empty()).then(|| pitch_svg_alternatives(&self.reading, &self.pitch_accent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pitch_svg() {
+        // 橋[2]: low, high, low particle
+        let svg = pitch_svg("はし", 2);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert!(svg.contains(r#"points="15,30 45,10 75,30""#));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches(r#"fill="white""#).count(), 1);
+
+        // きょ is one mora with one dot
+        let kyou = pitch_svg("きょう", 1);
+        assert_eq!(kyou.matches("<circle").count(), 3);
+        assert!(kyou.contains(">きょ</text>"));
+    }
+
+    #[test]
+    fn test_alternatives() {
+        let svg = pitch_svg_alternatives("はし", &[2, 0]);
+        assert_eq!(svg.matches("<g ").count(), 2);
+        assert!(svg.contains(r#"width="200""#));
+
+        let words = crate::find_with_nhk("はし");
+        let crate::FindWithNhkResult::MultipleMatches { homophones, .. } = words else {
+            panic!("expected several words read はし");
+        };
+        let bridge = homophones.iter().find(|w| w.text == "橋").unwrap();
+        assert_eq!(bridge.pitch_svg().unwrap(), pitch_svg_alternatives("はし", &bridge.pitch_accent));
+    }
+}
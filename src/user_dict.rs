@@ -0,0 +1,260 @@
+//! User dictionary entries for words JMDict lacks or ranks poorly.
+//!
+//! Entries are passed to [`crate::find_with_options`] and
+//! [`crate::find_with_nhk_options`] through [`crate::FindOptions::user_dictionary`].
+//! An entry with the same spelling and reading as a JMDict word overrides that
+//! word's frequency and pitch instead of adding a duplicate.
+
+use crate::kana_utils::katakana_to_hiragana;
+use crate::{PitchSource, WordFrequency, WordFrequencyWithPitch, WordSource};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// A user-registered word
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserEntry {
+    pub text: String,
+    pub reading: String,
+    /// Pitch accents in order of preference, `None` to use NHK data
+    pub pitch_accent: Option<Vec<u8>>,
+    /// Frequency score on the same scale as JMDict words, `None` for 0 or the JMDict score
+    pub frequency_score: Option<u32>,
+}
+
+impl UserEntry {
+    pub fn new(text: &str, reading: &str) -> Self {
+        UserEntry {
+            text: text.to_string(),
+            reading: reading.to_string(),
+            pitch_accent: None,
+            frequency_score: None,
+        }
+    }
+
+    pub fn with_pitch_accent(mut self, pitch_accent: Vec<u8>) -> Self {
+        self.pitch_accent = Some(pitch_accent);
+        self
+    }
+
+    pub fn with_frequency_score(mut self, frequency_score: u32) -> Self {
+        self.frequency_score = Some(frequency_score);
+        self
+    }
+}
+
+/// Failure to load a user dictionary file
+#[derive(Debug)]
+pub enum UserDictionaryError {
+    Io(std::io::Error),
+    /// A line that is not `surface<TAB>reading[<TAB>pitch[<TAB>frequency]]`
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for UserDictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserDictionaryError::Io(e) => write!(f, "cannot read user dictionary: {e}"),
+            UserDictionaryError::Parse { line, message } => write!(f, "user dictionary line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for UserDictionaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UserDictionaryError::Io(e) => Some(e),
+            UserDictionaryError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for UserDictionaryError {
+    fn from(e: std::io::Error) -> Self {
+        UserDictionaryError::Io(e)
+    }
+}
+
+/// User entries indexed by spelling and by reading
+#[derive(Debug, Clone, Default)]
+pub struct UserDictionary {
+    entries: Vec<UserEntry>,
+    by_text: HashMap<String, Vec<usize>>,
+    // Keyed by hiragana reading
+    by_reading: HashMap<String, Vec<usize>>,
+}
+
+impl UserDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry, replacing any earlier entry with the same spelling and reading
+    pub fn insert(&mut self, entry: UserEntry) {
+        if let Some(index) = self.position(&entry.text, &entry.reading) {
+            self.entries[index] = entry;
+            return;
+        }
+        let index = self.entries.len();
+        self.by_text.entry(entry.text.clone()).or_default().push(index);
+        self.by_reading.entry(katakana_to_hiragana(&entry.reading)).or_default().push(index);
+        self.entries.push(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Load a tab-separated file, see [`UserDictionary::parse`]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, UserDictionaryError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse tab-separated lines of `surface reading [pitch [frequency]]`
+    ///
+    /// Pitch is a comma-separated list such as `1,0`; leave it empty to keep
+    /// NHK data while still giving a frequency. Blank lines and lines starting
+    /// with `#` are ignored.
+    pub fn parse(source: &str) -> Result<Self, UserDictionaryError> {
+        let mut dictionary = UserDictionary::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| UserDictionaryError::Parse { line: line_number, message };
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            if fields.len() < 2 || fields[0].is_empty() || fields[1].is_empty() {
+                return Err(error("expected a surface and a reading".to_string()));
+            }
+            if fields.len() > 4 {
+                return Err(error(format!("expected at most 4 fields, found {}", fields.len())));
+            }
+
+            let mut entry = UserEntry::new(fields[0], fields[1]);
+            if let Some(pitch) = fields.get(2).filter(|f| !f.is_empty()) {
+                let pitches = pitch.split(',')
+                    .map(|p| p.trim().parse::<u8>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| error(format!("invalid pitch accent {pitch:?}")))?;
+                entry = entry.with_pitch_accent(pitches);
+            }
+            if let Some(frequency) = fields.get(3).filter(|f| !f.is_empty()) {
+                let score = frequency.parse::<u32>()
+                    .map_err(|_| error(format!("invalid frequency {frequency:?}")))?;
+                entry = entry.with_frequency_score(score);
+            }
+            dictionary.insert(entry);
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Entries spelled exactly `text`
+    pub fn with_text<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a UserEntry> + 'a {
+        self.lookup(self.by_text.get(text))
+    }
+
+    /// Entries read as `reading`, in hiragana or katakana
+    pub fn with_reading<'a>(&'a self, reading: &str) -> impl Iterator<Item = &'a UserEntry> + 'a {
+        self.lookup(self.by_reading.get(&katakana_to_hiragana(reading)))
+    }
+
+    /// The entry for one spelling and reading
+    pub fn get(&self, text: &str, reading: &str) -> Option<&UserEntry> {
+        self.position(text, reading).map(|i| &self.entries[i])
+    }
+
+    fn position(&self, text: &str, reading: &str) -> Option<usize> {
+        let reading = katakana_to_hiragana(reading);
+        self.by_text.get(text)?
+            .iter()
+            .copied()
+            .find(|&i| katakana_to_hiragana(&self.entries[i].reading) == reading)
+    }
+
+    fn lookup<'a>(&'a self, indices: Option<&'a Vec<usize>>) -> impl Iterator<Item = &'a UserEntry> + 'a {
+        indices.into_iter()
+            .flatten()
+            .map(|&i| &self.entries[i])
+    }
+
+    /// Apply frequency overrides to dictionary words
+    pub(crate) fn override_words(&self, words: &mut [WordFrequency]) {
+        for word in words {
+            if let Some(score) = self.get(&word.text, &word.reading).and_then(|e| e.frequency_score) {
+                word.frequency_score = score;
+                word.source = WordSource::User;
+            }
+        }
+    }
+
+    /// Apply frequency and pitch overrides to dictionary words
+    pub(crate) fn override_pitched_words(&self, words: &mut [WordFrequencyWithPitch]) {
+        for word in words {
+            let Some(entry) = self.get(&word.text, &word.reading) else {
+                continue;
+            };
+            if let Some(score) = entry.frequency_score {
+                word.frequency_score = score;
+                word.source = WordSource::User;
+            }
+            if let Some(pitches) = &entry.pitch_accent {
+                word.pitch_accent = pitches.clone();
+                word.pitch_source = PitchSource::User;
+                word.source = WordSource::User;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let dictionary = UserDictionary::parse("\
+# surface\treading\tpitch\tfrequency
+寛解\tかんかい\t0\t30000
+構成\tこうせい\t\t99999
+
+瑕疵\tかし\t1,0
+").unwrap();
+
+        assert_eq!(dictionary.len(), 3);
+        let kankai = dictionary.get("寛解", "かんかい").unwrap();
+        assert_eq!(kankai.pitch_accent, Some(vec![0]));
+        assert_eq!(kankai.frequency_score, Some(30000));
+        assert_eq!(dictionary.get("構成", "コウセイ").unwrap().pitch_accent, None);
+        assert_eq!(dictionary.with_reading("かし").next().unwrap().pitch_accent, Some(vec![1, 0]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            UserDictionary::parse("寛解\n"),
+            Err(UserDictionaryError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            UserDictionary::parse("# ok\n寛解\tかんかい\tx\n"),
+            Err(UserDictionaryError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut dictionary = UserDictionary::new();
+        dictionary.insert(UserEntry::new("寛解", "かんかい").with_frequency_score(1));
+        dictionary.insert(UserEntry::new("寛解", "かんかい").with_frequency_score(2));
+        assert_eq!(dictionary.len(), 1);
+        assert_eq!(dictionary.get("寛解", "かんかい").unwrap().frequency_score, Some(2));
+    }
+}
@@ -0,0 +1,56 @@
+//! WebAssembly bindings, enabled with the `wasm` feature.
+//!
+//! Results are returned as plain JS objects with the same field names as the
+//! Rust types. `FindWithNhkResult` carries its variant name in a `kind` field.
+
+use wasm_bindgen::prelude::*;
+
+/// Find homophones without pitch accent data
+#[wasm_bindgen]
+pub fn find(word: &str) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&crate::find(word))?)
+}
+
+/// Find homophones with pitch accent data
+#[wasm_bindgen(js_name = findWithNhk)]
+pub fn find_with_nhk(word: &str) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&crate::find_with_nhk(word))?)
+}
+
+/// Words whose reading or spelling starts with `prefix`, for autocomplete
+#[wasm_bindgen]
+pub fn suggest(prefix: &str, limit: usize) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&crate::suggest(prefix, limit))?)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn get(value: &JsValue, key: &str) -> JsValue {
+        js_sys::Reflect::get(value, &JsValue::from_str(key)).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_returns_array_of_words() {
+        let results = js_sys::Array::from(&find("かう").unwrap());
+        assert!(results.length() > 0);
+
+        let texts: Vec<String> = results.iter()
+            .map(|w| get(&w, "text").as_string().unwrap())
+            .collect();
+        assert!(texts.iter().any(|t| t == "買う"));
+        assert!(get(&results.get(0), "frequency_score").as_f64().is_some());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_with_nhk_kind() {
+        let result = find_with_nhk("構成").unwrap();
+        assert_eq!(get(&result, "kind").as_string().as_deref(), Some("UniqueMatch"));
+        assert!(js_sys::Array::is_array(&get(&result, "true_homophones")));
+
+        let result = find_with_nhk("こうせい").unwrap();
+        assert_eq!(get(&result, "kind").as_string().as_deref(), Some("MultipleMatches"));
+    }
+}